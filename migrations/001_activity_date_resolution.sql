@@ -0,0 +1,16 @@
+-- Track when an activity was first seen separately from its (estimated) activity date.
+ALTER TABLE bullshark_activities
+    ADD COLUMN IF NOT EXISTS first_seen_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS date_confidence TEXT NOT NULL DEFAULT 'first_seen';
+
+-- Existing rows were stamped with the ingest batch time, so that is all we know about them.
+UPDATE bullshark_activities
+SET first_seen_at = date
+WHERE first_seen_at IS NULL;
+
+ALTER TABLE bullshark_activities
+    ALTER COLUMN first_seen_at SET NOT NULL,
+    ALTER COLUMN first_seen_at SET DEFAULT NOW();
+
+CREATE INDEX IF NOT EXISTS bullshark_activities_first_seen_at_idx
+    ON bullshark_activities (first_seen_at);
@@ -0,0 +1,4 @@
+-- Link roster rows to the Strava athlete that authorized the app.
+-- Per-athlete tokens in strava_auth_tokens are keyed by this Strava id.
+ALTER TABLE athletes
+    ADD COLUMN IF NOT EXISTS strava_id TEXT UNIQUE;
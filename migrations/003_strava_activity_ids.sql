@@ -0,0 +1,4 @@
+-- Activities received through webhooks carry their real Strava id.
+-- Club feed activities don't expose one, so this stays NULL for them.
+ALTER TABLE bullshark_activities
+    ADD COLUMN IF NOT EXISTS strava_activity_id BIGINT UNIQUE;
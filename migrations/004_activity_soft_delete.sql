@@ -0,0 +1,9 @@
+-- Edited or deleted Strava activities are soft-deleted instead of being counted twice.
+ALTER TABLE bullshark_activities
+    ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS deleted_reason TEXT,
+    ADD COLUMN IF NOT EXISTS superseded_by TEXT;
+
+CREATE INDEX IF NOT EXISTS bullshark_activities_live_date_idx
+    ON bullshark_activities (date)
+    WHERE deleted_at IS NULL;
@@ -0,0 +1,25 @@
+-- Competitions replace the hard-coded team stats window and team names.
+CREATE TABLE IF NOT EXISTS competitions (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    start_date TIMESTAMPTZ NOT NULL,
+    end_date TIMESTAMPTZ,
+    timezone TEXT NOT NULL DEFAULT 'America/Los_Angeles',
+    teams TEXT[] NOT NULL,
+    sport_types TEXT[] NOT NULL DEFAULT ARRAY['Run'],
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (end_date IS NULL OR end_date > start_date)
+);
+
+-- The season that was previously hard-coded in ActivityController::get_team_stat_dates
+INSERT INTO competitions (id, name, start_date, end_date, timezone, teams, sport_types)
+VALUES (
+    'bulls-vs-sharks-2025',
+    'Bulls vs Sharks 2025',
+    '2025-12-01 00:00:00 America/Los_Angeles',
+    NULL,
+    'America/Los_Angeles',
+    ARRAY['bulls', 'sharks'],
+    ARRAY['Run']
+)
+ON CONFLICT (id) DO NOTHING;
@@ -0,0 +1,12 @@
+-- Teams get a display name and colour, and competitions can have any number of them.
+CREATE TABLE IF NOT EXISTS teams (
+    id TEXT PRIMARY KEY,
+    display_name TEXT NOT NULL,
+    color TEXT
+);
+
+-- Seed from the team values already on the roster
+INSERT INTO teams (id, display_name)
+SELECT DISTINCT LOWER(team), INITCAP(team)
+FROM athletes
+ON CONFLICT (id) DO NOTHING;
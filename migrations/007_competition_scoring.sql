@@ -0,0 +1,4 @@
+-- Each competition picks how activities are scored. Distance in kilometres matches the old behaviour.
+ALTER TABLE competitions
+    ADD COLUMN IF NOT EXISTS scoring JSONB NOT NULL
+    DEFAULT '{"rule": {"type": "distance"}, "sport_multipliers": {}}'::jsonb;
@@ -0,0 +1,9 @@
+-- Raw Strava names (e.g. after a member renames themselves) bound to a roster athlete.
+CREATE TABLE IF NOT EXISTS athlete_aliases (
+    alias TEXT NOT NULL,
+    athlete_id TEXT NOT NULL REFERENCES athletes(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Aliases are matched case-insensitively
+CREATE UNIQUE INDEX IF NOT EXISTS athlete_aliases_alias_idx ON athlete_aliases (LOWER(alias));
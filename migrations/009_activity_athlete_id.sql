@@ -0,0 +1,6 @@
+-- Activities point at the roster athlete they were resolved to, instead of relying on the raw name.
+-- Existing rows are filled in by POST /activities/backfill_athletes.
+ALTER TABLE bullshark_activities
+    ADD COLUMN IF NOT EXISTS athlete_id TEXT REFERENCES athletes(id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS bullshark_activities_athlete_id_idx ON bullshark_activities (athlete_id, date);
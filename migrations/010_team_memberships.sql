@@ -0,0 +1,19 @@
+-- Which team an athlete was on, and when. athletes.team keeps the current team.
+CREATE TABLE IF NOT EXISTS team_memberships (
+    id BIGSERIAL PRIMARY KEY,
+    athlete_id TEXT NOT NULL REFERENCES athletes(id) ON DELETE CASCADE,
+    team TEXT NOT NULL,
+    valid_from TIMESTAMPTZ NOT NULL,
+    valid_to TIMESTAMPTZ,
+    CHECK (valid_to IS NULL OR valid_to > valid_from)
+);
+
+-- Each athlete has exactly one open membership
+CREATE UNIQUE INDEX IF NOT EXISTS team_memberships_current_idx ON team_memberships (athlete_id) WHERE valid_to IS NULL;
+CREATE INDEX IF NOT EXISTS team_memberships_athlete_idx ON team_memberships (athlete_id, valid_from);
+
+-- Everyone already on the roster has been on their team from the start
+INSERT INTO team_memberships (athlete_id, team, valid_from)
+SELECT id, LOWER(team), 'epoch'::timestamptz
+FROM athletes
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,16 @@
+-- Distance or moving time targets an athlete sets for every week, every month, or one competition.
+CREATE TABLE IF NOT EXISTS goals (
+    id TEXT PRIMARY KEY,
+    athlete_id TEXT NOT NULL REFERENCES athletes(id) ON DELETE CASCADE,
+    metric TEXT NOT NULL,
+    period TEXT NOT NULL,
+    -- Kilometres for distance goals, minutes for moving time goals
+    target DOUBLE PRECISION NOT NULL CHECK (target > 0),
+    competition_id TEXT REFERENCES competitions(id) ON DELETE CASCADE,
+    sport_types TEXT[] NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    archived_at TIMESTAMPTZ,
+    CHECK ((period = 'competition') = (competition_id IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS goals_athlete_idx ON goals (athlete_id, created_at);
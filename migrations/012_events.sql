@@ -0,0 +1,30 @@
+-- Races athletes train for. athletes.event now references an event id.
+CREATE TABLE IF NOT EXISTS events (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    -- Race day in Pacific time, NULL for generic distances without a scheduled race
+    date DATE,
+    -- Metres
+    distance DOUBLE PRECISION CHECK (distance IS NULL OR distance > 0)
+);
+
+-- The generic distances athletes could pick before events existed
+INSERT INTO events (id, name, distance)
+VALUES
+    ('5K', '5K', 5000),
+    ('10K', '10K', 10000),
+    ('Half Marathon', 'Half Marathon', 21097.5),
+    ('Marathon', 'Marathon', 42195),
+    ('Ultra', 'Ultra', NULL)
+ON CONFLICT (id) DO NOTHING;
+
+-- Keep any other value already on the roster so the foreign key holds
+INSERT INTO events (id, name)
+SELECT DISTINCT event, event
+FROM athletes
+ON CONFLICT (id) DO NOTHING;
+
+ALTER TABLE athletes
+    ADD CONSTRAINT athletes_event_fkey FOREIGN KEY (event) REFERENCES events(id) ON UPDATE CASCADE;
+
+CREATE INDEX IF NOT EXISTS athletes_event_idx ON athletes (event);
@@ -0,0 +1,13 @@
+-- One row per completed club sync, including syncs that found nothing new.
+-- Date estimates spread new uploads back to the previous run, not the previous insert.
+CREATE TABLE IF NOT EXISTS sync_runs (
+    started_at TIMESTAMPTZ PRIMARY KEY,
+    new_activity_count INTEGER NOT NULL DEFAULT 0
+);
+
+-- Until the first run under this schema, the last insert is the best guess we have
+INSERT INTO sync_runs (started_at)
+SELECT MAX(first_seen_at)
+FROM bullshark_activities
+HAVING MAX(first_seen_at) IS NOT NULL
+ON CONFLICT (started_at) DO NOTHING;
@@ -0,0 +1,10 @@
+-- One-time links an admin hands to an athlete so they can connect their own Strava account.
+-- replace_strava_id is the admin's confirmation that an existing link may be overwritten.
+CREATE TABLE IF NOT EXISTS strava_invites (
+    token TEXT PRIMARY KEY,
+    athlete_id TEXT NOT NULL REFERENCES athletes(id) ON DELETE CASCADE,
+    replace_strava_id BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_at TIMESTAMPTZ
+);
@@ -0,0 +1,3 @@
+-- Event ids are matched case-insensitively, so "5k" and "5K" can't both exist.
+-- Fails if they already do; merge the duplicates first.
+CREATE UNIQUE INDEX IF NOT EXISTS events_lower_id_idx ON events (LOWER(id));
@@ -1,17 +1,17 @@
 use std::{sync::Arc};
 
 use axum::{Json, extract::{Query, State}, http::{StatusCode, HeaderMap}};
-use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
-use chrono_tz::America::Los_Angeles;
-use serde::Deserialize;
+use chrono::Utc;
 
-use crate::{error::ApiError, models::{bullshark::BullSharkActivity, team_stats::TeamStats}, services::{activity_controller::ActivityController, database::Database}};
+use crate::{error::ApiError, models::{bullshark::BullSharkActivity, consistency::CompetitionConsistency, pagination::{ActivityList, ActivityListQuery}, team_stats::{MultiTeamStats, TeamStats}}, services::{activity_controller::ActivityController, database::Database}, utils::{auth_utils, time_utils}};
 
 pub async fn read_activities(
+    Query(query): Query<ActivityListQuery>,
     State(db): State<Arc<Database>>
-) -> Result<Json<Vec<BullSharkActivity>>, ApiError> {
-    let activities = db.get_all_activities().await?;
-    Ok(Json(activities))
+) -> Result<Json<ActivityList<BullSharkActivity>>, ApiError> {
+    let filter = query.into_filter()?;
+    let activities = db.get_activity_page(&filter).await?;
+    Ok(Json(filter.get_list(activities)))
 }
 
 pub async fn populate_activities(
@@ -40,109 +40,63 @@ pub async fn populate_activities(
     Ok(StatusCode::OK)
 }
 
+#[derive(serde::Serialize)]
+pub struct BackfillResult {
+    updated: u64,
+}
+
+pub async fn backfill_activity_athletes(
+    headers: HeaderMap,
+    State(controller): State<Arc<ActivityController>>
+) -> Result<Json<BackfillResult>, ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    let updated = controller.backfill_athlete_ids().await?;
+    Ok(Json(BackfillResult { updated }))
+}
+
 pub async fn get_activities_from_this_week(
+    Query(query): Query<ActivityListQuery>,
     State(db): State<Arc<Database>>
-) -> Result<Json<Vec<BullSharkActivity>>, ApiError> {
-    // Get current time in Pacific timezone
-    let now_pacific = Los_Angeles.from_utc_datetime(&Utc::now().naive_utc());
-
-    // Calculate start of week (Sunday 00:00:00) in Pacific
-    let days_since_monday= now_pacific.weekday().num_days_from_monday();
-    let start_of_week_pacific = now_pacific
-        .date_naive()
-        .and_hms_opt(0, 0, 0)
-        .unwrap()
-        - Duration::days(days_since_monday as i64);
-    let start_of_week_pacific = Los_Angeles.from_local_datetime(&start_of_week_pacific).single()
-        .ok_or_else(|| ApiError::InternalConversionError("Invalid start of week time".to_string()))?;
-
-    // Calculate end of week (Saturday 23:59:59) in Pacific
-    let end_of_week_pacific = start_of_week_pacific
-        .date_naive()
-        .and_hms_opt(23, 59, 59)
-        .unwrap()
-        + Duration::days(6);
-    let end_of_week_pacific = Los_Angeles.from_local_datetime(&end_of_week_pacific).single()
-        .ok_or_else(|| ApiError::InternalConversionError("Invalid end of week time".to_string()))?;
-
-    // Convert to UTC for database query
-    let start_utc = start_of_week_pacific.with_timezone(&Utc);
-    let end_utc = end_of_week_pacific.with_timezone(&Utc);
+) -> Result<Json<ActivityList<BullSharkActivity>>, ApiError> {
+    let (start_utc, end_utc) = time_utils::get_week_window(Utc::now())?;
 
     println!("[API] get_activities_from_this_week: Querying from {} to {}", start_utc, end_utc);
 
     // Query database
-    let activities = db.get_activities_from_window(start_utc, end_utc).await?;
-    Ok(Json(activities))
+    let filter = query.into_filter()?.within(start_utc, end_utc);
+    let activities = db.get_activity_page(&filter).await?;
+    Ok(Json(filter.get_list(activities)))
 }
 
 pub async fn get_activities_from_this_month(
+    Query(query): Query<ActivityListQuery>,
     State(db): State<Arc<Database>>
-) -> Result<Json<Vec<BullSharkActivity>>, ApiError> {
-    // Get current time in Pacific timezone
-    let now_pacific = Los_Angeles.from_utc_datetime(&Utc::now().naive_utc());
-
-    // Calculate start of month (1st day at 00:00:00) in Pacific
-    let start_of_month_pacific = now_pacific
-        .date_naive()
-        .with_day(1)
-        .ok_or_else(|| ApiError::InternalConversionError("Invalid start of month date".to_string()))?
-        .and_hms_opt(0, 0, 0)
-        .unwrap();
-    let start_of_month_pacific = Los_Angeles.from_local_datetime(&start_of_month_pacific).single()
-        .ok_or_else(|| ApiError::InternalConversionError("Invalid start of month time".to_string()))?;
-
-    // Calculate end of month (last day at 23:59:59) in Pacific
-    // Get the first day of next month, then subtract 1 second to get end of current month
-    let next_month = if now_pacific.month() == 12 {
-        now_pacific.date_naive()
-            .with_year(now_pacific.year() + 1)
-            .and_then(|d| d.with_month(1))
-    } else {
-        now_pacific.date_naive()
-            .with_month(now_pacific.month() + 1)
-    }
-    .ok_or_else(|| ApiError::InternalConversionError("Invalid next month date".to_string()))?
-    .and_hms_opt(0, 0, 0)
-    .unwrap();
-
-    let end_of_month_pacific = Los_Angeles.from_local_datetime(&next_month).single()
-        .ok_or_else(|| ApiError::InternalConversionError("Invalid end of month time".to_string()))?
-        - Duration::seconds(1);
-
-    // Convert to UTC for database query
-    let start_utc = start_of_month_pacific.with_timezone(&Utc);
-    let end_utc = end_of_month_pacific.with_timezone(&Utc);
+) -> Result<Json<ActivityList<BullSharkActivity>>, ApiError> {
+    let (start_utc, end_utc) = time_utils::get_month_window(Utc::now())?;
 
     println!("[API] get_activities_from_this_month: Querying from {} to {}", start_utc, end_utc);
 
     // Query database
-    let activities = db.get_activities_from_window(start_utc, end_utc).await?;
-    Ok(Json(activities))
-}
-
-#[derive(Deserialize)]
-pub struct WindowQuery {
-    start: String,
-    end: String,
+    let filter = query.into_filter()?.within(start_utc, end_utc);
+    let activities = db.get_activity_page(&filter).await?;
+    Ok(Json(filter.get_list(activities)))
 }
 
 pub async fn get_activities_from_custom_window(
-    Query(params): Query<WindowQuery>,
+    Query(query): Query<ActivityListQuery>,
     State(db): State<Arc<Database>>
-) -> Result<Json<Vec<BullSharkActivity>>, ApiError> {
-    // Parse the datetime strings into DateTime<Utc>
-    let start_utc = params.start.parse::<DateTime<Utc>>()
-        .map_err(|e| ApiError::BadRequest(format!("Invalid start datetime format: {}. Expected RFC3339 format (e.g., 2024-01-01T00:00:00Z)", e)))?;
-
-    let end_utc = params.end.parse::<DateTime<Utc>>()
-        .map_err(|e| ApiError::BadRequest(format!("Invalid end datetime format: {}. Expected RFC3339 format (e.g., 2024-01-31T23:59:59Z)", e)))?;
-
-    println!("[API] get_activities_from_custom_window: Querying from {} to {}", start_utc, end_utc);
+) -> Result<Json<ActivityList<BullSharkActivity>>, ApiError> {
+    // Unlike the other list routes, the window is required here
+    let (Some(start), Some(end)) = (&query.start, &query.end) else {
+        return Err(ApiError::BadRequest("start and end are required. Expected RFC3339 format (e.g., 2024-01-01T00:00:00Z)".to_string()));
+    };
+    println!("[API] get_activities_from_custom_window: Querying from {} to {}", start, end);
 
     // Query database
-    let activities = db.get_activities_from_window(start_utc, end_utc).await?;
-    Ok(Json(activities))
+    let filter = query.into_filter()?;
+    let activities = db.get_activity_page(&filter).await?;
+    Ok(Json(filter.get_list(activities)))
 }
 
 pub async fn get_team_stats(
@@ -152,3 +106,19 @@ pub async fn get_team_stats(
 
     Ok(Json(team_stats))
 }
+
+pub async fn get_team_consistency(
+    State(activity_controller): State<Arc<ActivityController>>,
+) -> Result<Json<CompetitionConsistency>, ApiError> {
+    let consistency = activity_controller.get_current_consistency().await?;
+
+    Ok(Json(consistency))
+}
+
+pub async fn get_all_team_stats(
+    State(activity_controller): State<Arc<ActivityController>>,
+) -> Result<Json<MultiTeamStats>, ApiError> {
+    let team_stats = activity_controller.get_current_team_stats().await?;
+
+    Ok(Json(team_stats))
+}
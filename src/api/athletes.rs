@@ -1,8 +1,9 @@
 use std::sync::Arc;
 
-use axum::{Json, extract::State};
+use axum::{Json, extract::{Path, Query, State}, http::{HeaderMap, StatusCode}};
+use chrono::Utc;
 
-use crate::{error::ApiError, models::athlete::Athlete, services::{database::Database}};
+use crate::{error::ApiError, models::{athlete_profile::{AthleteProfile, AthleteProfileQuery}, bullshark::BullSharkActivity, pagination::{ActivityList, ActivityListQuery}, athlete::{Athlete, AthleteAlias, AthleteInput, RosterUploadResult, TeamMembership, TransferRequest, UnmatchedAthleteName, UpdateAthlete}, team::Team}, services::{activity_controller::ActivityController, athlete_controller::AthleteController, athlete_resolver::AthleteResolver, database::Database}, utils::{auth_utils, roster_utils}};
 
 pub async fn get_athletes(
     State(db): State<Arc<Database>>
@@ -10,3 +11,194 @@ pub async fn get_athletes(
     let result = db.read_all_athletes().await?;
     Ok(Json(result))
 }
+
+pub async fn get_athlete_profile(
+    Path(id): Path<String>,
+    Query(query): Query<AthleteProfileQuery>,
+    State(athlete_controller): State<Arc<AthleteController>>
+) -> Result<Json<AthleteProfile>, ApiError> {
+    let profile = athlete_controller.get_profile(&id, query).await?;
+    Ok(Json(profile))
+}
+
+pub async fn create_athlete(
+    headers: HeaderMap,
+    State(db): State<Arc<Database>>,
+    State(activity_controller): State<Arc<ActivityController>>,
+    Json(input): Json<AthleteInput>
+) -> Result<(StatusCode, Json<Athlete>), ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    let teams = db.read_all_teams().await?;
+    let events = db.read_all_events().await?;
+    let athlete = input.into_athlete(&teams, &events).map_err(ApiError::BadRequest)?;
+    db.insert_athlete(&athlete).await?;
+    activity_controller.spawn_backfill_athlete_ids();
+
+    println!("[API] create_athlete: Added athlete '{}' ({})", athlete.name, athlete.id);
+    Ok((StatusCode::CREATED, Json(athlete)))
+}
+
+pub async fn update_athlete(
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    State(db): State<Arc<Database>>,
+    State(activity_controller): State<Arc<ActivityController>>,
+    Json(update): Json<UpdateAthlete>
+) -> Result<Json<Athlete>, ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    let teams = db.read_all_teams().await?;
+    let events = db.read_all_events().await?;
+    let input = AthleteInput {
+        id,
+        name: update.name,
+        team: update.team,
+        event: update.event,
+    };
+    let mut athlete = input.into_athlete(&teams, &events).map_err(ApiError::BadRequest)?;
+    db.update_athlete(&athlete).await?;
+    activity_controller.spawn_backfill_athlete_ids();
+
+    athlete.strava_id = db.read_athlete(&athlete.id).await?.and_then(|a| a.strava_id);
+    Ok(Json(athlete))
+}
+
+pub async fn delete_athlete(
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    State(db): State<Arc<Database>>
+) -> Result<StatusCode, ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    db.delete_athlete(&id).await?;
+    println!("[API] delete_athlete: Removed athlete {}", id);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn upload_athletes_csv(
+    headers: HeaderMap,
+    State(db): State<Arc<Database>>,
+    State(activity_controller): State<Arc<ActivityController>>,
+    body: String
+) -> Result<Json<RosterUploadResult>, ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    let teams = db.read_all_teams().await?;
+    let events = db.read_all_events().await?;
+    let (athletes, errors) = roster_utils::parse_roster_csv(&body, &teams, &events).map_err(ApiError::BadRequest)?;
+    let upserted = db.upsert_athletes(&athletes).await?;
+    activity_controller.spawn_backfill_athlete_ids();
+
+    println!("[API] upload_athletes_csv: Upserted {} athletes, skipped {} rows", upserted, errors.len());
+    Ok(Json(RosterUploadResult {
+        upserted: upserted as usize,
+        errors,
+    }))
+}
+
+pub async fn get_unmatched_athletes(
+    State(db): State<Arc<Database>>
+) -> Result<Json<Vec<UnmatchedAthleteName>>, ApiError> {
+    let resolver = AthleteResolver::load(&db).await?;
+    let unmatched: Vec<UnmatchedAthleteName> = db.read_activity_athlete_names().await?
+        .into_iter()
+        .filter(|(name, _, _)| resolver.resolve(name).is_none())
+        .map(|(name, activity_count, last_seen)| UnmatchedAthleteName {
+            candidates: resolver.get_candidates(&name),
+            name,
+            activity_count,
+            last_seen,
+        })
+        .collect();
+
+    Ok(Json(unmatched))
+}
+
+pub async fn get_athlete_aliases(
+    State(db): State<Arc<Database>>
+) -> Result<Json<Vec<AthleteAlias>>, ApiError> {
+    let aliases = db.read_all_athlete_aliases().await?;
+    Ok(Json(aliases))
+}
+
+pub async fn get_athlete_activities(
+    Path(id): Path<String>,
+    Query(query): Query<ActivityListQuery>,
+    State(db): State<Arc<Database>>
+) -> Result<Json<ActivityList<BullSharkActivity>>, ApiError> {
+    if db.read_athlete(&id).await?.is_none() {
+        return Err(ApiError::NotFound(format!("No athlete found with id: {}", id)));
+    }
+    let mut filter = query.into_filter()?;
+    filter.athlete_id = Some(id);
+    let activities = db.get_activity_page(&filter).await?;
+    Ok(Json(filter.get_list(activities)))
+}
+
+pub async fn bind_athlete_alias(
+    headers: HeaderMap,
+    State(db): State<Arc<Database>>,
+    State(activity_controller): State<Arc<ActivityController>>,
+    Json(alias): Json<AthleteAlias>
+) -> Result<Json<AthleteAlias>, ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    let name = alias.alias.split_whitespace().collect::<Vec<&str>>().join(" ");
+    if name.is_empty() {
+        return Err(ApiError::BadRequest("Alias is required".to_string()));
+    }
+    if db.read_athlete(&alias.athlete_id).await?.is_none() {
+        return Err(ApiError::NotFound(format!("No athlete found with id: {}", alias.athlete_id)));
+    }
+
+    let alias = AthleteAlias {
+        alias: name,
+        athlete_id: alias.athlete_id,
+    };
+    db.upsert_athlete_alias(&alias).await?;
+
+    // Attribute activities already stored under this name
+    activity_controller.spawn_backfill_athlete_ids();
+    Ok(Json(alias))
+}
+
+pub async fn get_team_memberships(
+    Path(id): Path<String>,
+    State(db): State<Arc<Database>>
+) -> Result<Json<Vec<TeamMembership>>, ApiError> {
+    if db.read_athlete(&id).await?.is_none() {
+        return Err(ApiError::NotFound(format!("No athlete found with id: {}", id)));
+    }
+    let memberships = db.read_team_memberships(&id).await?;
+    Ok(Json(memberships))
+}
+
+pub async fn transfer_athlete(
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    State(db): State<Arc<Database>>,
+    Json(transfer): Json<TransferRequest>
+) -> Result<Json<Vec<TeamMembership>>, ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    if db.read_athlete(&id).await?.is_none() {
+        return Err(ApiError::NotFound(format!("No athlete found with id: {}", id)));
+    }
+    let team_id = Team::normalize_id(&transfer.team);
+    let team = db.read_all_teams().await?
+        .into_iter()
+        .find(|team| team.id == team_id)
+        .ok_or_else(|| ApiError::BadRequest(format!("Unknown team: {}", transfer.team)))?;
+
+    let effective_at = transfer.effective_at.unwrap_or_else(Utc::now);
+    if effective_at > Utc::now() {
+        return Err(ApiError::BadRequest("Transfers can't be scheduled in the future".to_string()));
+    }
+
+    db.transfer_athlete(&id, &team.id, effective_at).await?;
+    println!("[API] transfer_athlete: Moved athlete {} to {} from {}", id, team.id, effective_at);
+
+    let memberships = db.read_team_memberships(&id).await?;
+    Ok(Json(memberships))
+}
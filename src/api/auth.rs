@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::{Query, State}, http::{HeaderMap, StatusCode}, response::Redirect};
+use serde::Deserialize;
+
+use crate::{error::ApiError, models::invite::{CreateStravaInvite, StravaConnection, StravaInviteLink}, services::auth_controller::AuthController, utils::auth_utils};
+
+pub async fn create_strava_invite(
+    headers: HeaderMap,
+    State(auth_controller): State<Arc<AuthController>>,
+    Json(input): Json<CreateStravaInvite>
+) -> Result<(StatusCode, Json<StravaInviteLink>), ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    let link = auth_controller.create_invite(input).await?;
+    Ok((StatusCode::CREATED, Json(link)))
+}
+
+#[derive(Deserialize)]
+pub struct LoginQuery {
+    invite: String,
+}
+
+pub async fn strava_login(
+    Query(params): Query<LoginQuery>,
+    State(auth_controller): State<Arc<AuthController>>
+) -> Result<Redirect, ApiError> {
+    println!("[API] strava_login: Starting Strava authorization from an invite");
+    let authorize_url = auth_controller.build_authorize_url(&params.invite).await?;
+    Ok(Redirect::to(&authorize_url))
+}
+
+#[derive(Deserialize)]
+pub struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    scope: Option<String>,
+    error: Option<String>,
+}
+
+pub async fn strava_callback(
+    Query(params): Query<CallbackQuery>,
+    State(auth_controller): State<Arc<AuthController>>
+) -> Result<Json<StravaConnection>, ApiError> {
+    // Strava redirects with error=access_denied when the athlete cancels
+    if let Some(error) = params.error {
+        return Err(ApiError::BadRequest(format!("Strava authorization was not granted: {}", error)));
+    }
+
+    let code = params.code
+        .ok_or_else(|| ApiError::BadRequest("Missing authorization code".to_string()))?;
+    let state = params.state
+        .ok_or_else(|| ApiError::BadRequest("Missing OAuth state".to_string()))?;
+    let scope = params.scope.unwrap_or_default();
+
+    let connection = auth_controller.connect_athlete(&code, &state, &scope).await?;
+    Ok(Json(connection))
+}
@@ -0,0 +1,57 @@
+use std::{collections::{BTreeMap, HashMap}, sync::Arc};
+
+use axum::{extract::{Query, State}, response::Response};
+use chrono::{Duration, Utc};
+
+use crate::{error::ApiError, models::{calendar::{CalendarQuery, CalendarRace}, export::ExportQuery, team::Team}, services::database::Database, utils::{calendar_utils, time_utils}};
+
+// Calendar apps re-download the whole feed on every refresh, so keep the default window short
+const DEFAULT_CALENDAR_DAYS: i64 = 90;
+
+pub async fn get_calendar(
+    Query(query): Query<CalendarQuery>,
+    State(db): State<Arc<Database>>
+) -> Result<Response, ApiError> {
+    let start = match query.start.as_deref() {
+        Some(start) => time_utils::parse_datetime("start", start)?,
+        None => Utc::now() - Duration::days(DEFAULT_CALENDAR_DAYS),
+    };
+    let end = query.end.as_deref().map(|end| time_utils::parse_datetime("end", end)).transpose()?;
+    if end.is_some_and(|end| end < start) {
+        return Err(ApiError::BadRequest("end must be after start".to_string()));
+    }
+
+    // Races come from the events the matching athletes are training for, matched on their current team's id
+    let team = query.team.as_deref().map(Team::normalize_id);
+    let current_teams: HashMap<String, String> = db.read_all_team_memberships().await?
+        .into_iter()
+        .filter(|(_, membership)| membership.valid_to.is_none())
+        .map(|(athlete_id, membership)| (athlete_id, Team::normalize_id(&membership.team)))
+        .collect();
+    let mut athlete_names: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for athlete in db.read_all_athletes().await? {
+        let athlete_team = current_teams.get(&athlete.id).cloned().unwrap_or_else(|| Team::normalize_id(&athlete.team));
+        let team_matches = team.as_ref().is_none_or(|team| athlete_team == *team);
+        let athlete_matches = query.athlete_id.as_ref().is_none_or(|id| athlete.id == *id);
+        if team_matches && athlete_matches {
+            athlete_names.entry(athlete.event.to_lowercase()).or_default().push(athlete.name);
+        }
+    }
+    let races: Vec<CalendarRace> = db.read_all_events().await?
+        .into_iter()
+        .filter(|event| event.date.is_some())
+        .filter_map(|event| athlete_names
+            .remove(&event.id.to_lowercase())
+            .map(|athlete_names| CalendarRace { event, athlete_names }))
+        .collect();
+
+    println!("[API] get_calendar: Rendering {} races and activities from {} to {:?}", races.len(), start, end);
+    let export_query = ExportQuery {
+        team: query.team,
+        athlete_id: query.athlete_id,
+        sport_type: query.sport_type,
+        ..Default::default()
+    };
+    let receiver = db.stream_activities(&export_query, Some(start), end);
+    Ok(calendar_utils::stream_calendar(races, receiver))
+}
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::{Path, State}, http::{HeaderMap, StatusCode}};
+
+use crate::{error::ApiError, models::{competition::{Competition, CreateCompetition}, consistency::CompetitionConsistency, scoring::PointsBreakdown, team_stats::MultiTeamStats}, services::{activity_controller::ActivityController, database::Database}, utils::auth_utils};
+
+pub async fn create_competition(
+    headers: HeaderMap,
+    State(db): State<Arc<Database>>,
+    Json(request): Json<CreateCompetition>
+) -> Result<(StatusCode, Json<Competition>), ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    let competition = request.into_competition(uuid::Uuid::new_v4().to_string())?;
+    db.insert_competition(&competition).await?;
+
+    println!("[API] create_competition: Created competition '{}' ({})", competition.name, competition.id);
+    Ok((StatusCode::CREATED, Json(competition)))
+}
+
+pub async fn get_competitions(
+    State(db): State<Arc<Database>>
+) -> Result<Json<Vec<Competition>>, ApiError> {
+    let competitions = db.read_all_competitions().await?;
+    Ok(Json(competitions))
+}
+
+pub async fn get_competition(
+    Path(id): Path<String>,
+    State(db): State<Arc<Database>>
+) -> Result<Json<Competition>, ApiError> {
+    let competition = db.read_competition(&id).await?
+        .ok_or_else(|| ApiError::NotFound(format!("No competition found with id: {}", id)))?;
+    Ok(Json(competition))
+}
+
+pub async fn get_competition_team_stats(
+    Path(id): Path<String>,
+    State(activity_controller): State<Arc<ActivityController>>
+) -> Result<Json<MultiTeamStats>, ApiError> {
+    let team_stats = activity_controller.get_competition_team_stats(&id).await?;
+    Ok(Json(team_stats))
+}
+
+pub async fn explain_activity_points(
+    Path((id, activity_id)): Path<(String, String)>,
+    State(activity_controller): State<Arc<ActivityController>>
+) -> Result<Json<PointsBreakdown>, ApiError> {
+    let breakdown = activity_controller.explain_activity_points(&id, &activity_id).await?;
+    Ok(Json(breakdown))
+}
+
+pub async fn get_competition_consistency(
+    Path(id): Path<String>,
+    State(activity_controller): State<Arc<ActivityController>>
+) -> Result<Json<CompetitionConsistency>, ApiError> {
+    let consistency = activity_controller.get_competition_consistency(&id).await?;
+    Ok(Json(consistency))
+}
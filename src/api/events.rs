@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::{Path, Query, State}, http::HeaderMap};
+
+use crate::{error::ApiError, models::event::{EventDashboard, EventDashboardQuery, RaceEvent}, services::{database::Database, event_controller::EventController}, utils::auth_utils};
+
+pub async fn get_events(
+    State(db): State<Arc<Database>>
+) -> Result<Json<Vec<RaceEvent>>, ApiError> {
+    let events = db.read_all_events().await?;
+    Ok(Json(events))
+}
+
+pub async fn upsert_event(
+    headers: HeaderMap,
+    State(db): State<Arc<Database>>,
+    Json(event): Json<RaceEvent>
+) -> Result<Json<RaceEvent>, ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    let event = event.validate()?;
+    db.upsert_event(&event).await?;
+    Ok(Json(event))
+}
+
+pub async fn get_event_dashboard(
+    Path(id): Path<String>,
+    Query(query): Query<EventDashboardQuery>,
+    State(event_controller): State<Arc<EventController>>
+) -> Result<Json<EventDashboard>, ApiError> {
+    let dashboard = event_controller.get_dashboard(&id, query).await?;
+    Ok(Json(dashboard))
+}
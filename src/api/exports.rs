@@ -0,0 +1,49 @@
+use std::sync::Arc;
+
+use axum::{extract::{Query, State}, http::HeaderMap, response::Response};
+
+use crate::{error::ApiError, models::{export::{ActivityCsvRow, ExportQuery}, team::Team}, services::{activity_controller::ActivityController, database::Database}, utils::{export_utils, time_utils}};
+
+pub async fn export_activities(
+    headers: HeaderMap,
+    Query(query): Query<ExportQuery>,
+    State(db): State<Arc<Database>>
+) -> Result<Response, ApiError> {
+    let format = export_utils::get_export_format(query.format.as_deref(), &headers)?;
+    let start = query.start.as_deref().map(|start| time_utils::parse_datetime("start", start)).transpose()?;
+    let end = query.end.as_deref().map(|end| time_utils::parse_datetime("end", end)).transpose()?;
+
+    println!("[API] export_activities: Exporting {:?} from {:?} to {:?}", format, start, end);
+    let receiver = db.stream_activities(&query, start, end);
+    Ok(export_utils::stream_export(receiver, format, "activities", ActivityCsvRow::from))
+}
+
+pub async fn export_team_stats(
+    headers: HeaderMap,
+    Query(query): Query<ExportQuery>,
+    State(db): State<Arc<Database>>,
+    State(activity_controller): State<Arc<ActivityController>>
+) -> Result<Response, ApiError> {
+    let format = export_utils::get_export_format(query.format.as_deref(), &headers)?;
+    let start = query.start.as_deref().map(|start| time_utils::parse_datetime("start", start)).transpose()?;
+    let end = query.end.as_deref().map(|end| time_utils::parse_datetime("end", end)).transpose()?;
+
+    // The rows add up to the competition's team stats, so the competition decides the teams, sports and timezone
+    let competition = match &query.competition_id {
+        Some(id) => db.read_competition(id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No competition found with id: {}", id)))?,
+        None => db.read_current_competition().await?
+            .ok_or_else(|| ApiError::NotFound("No competition has started yet".to_string()))?,
+    };
+    let (competition_start, competition_end) = competition.get_window();
+    let (start, end) = (start.unwrap_or(competition_start), end.unwrap_or(competition_end));
+    if end < start {
+        return Err(ApiError::BadRequest("end must be after start".to_string()));
+    }
+    let team = query.team.as_deref().map(Team::normalize_id);
+
+    // Computed before responding so errors, like a bad timezone, still get their status code
+    println!("[API] export_team_stats: Exporting {:?} from {} to {}", format, start, end);
+    let rows = activity_controller.get_team_week_rows(&competition, start, end, query.sport_type.as_deref(), team.as_deref()).await?;
+    Ok(export_utils::stream_export(export_utils::get_row_receiver(rows), format, "team_stats", std::convert::identity))
+}
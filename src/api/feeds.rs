@@ -0,0 +1,56 @@
+use std::sync::Arc;
+
+use axum::{extract::{Query, State}, response::Response};
+use chrono::Utc;
+
+use crate::{error::ApiError, models::{feed::{ActivityFeedQuery, AtomFeed, TeamResultsFeedQuery}, pagination::ActivityListQuery}, services::{activity_controller::ActivityController, database::Database}, utils::feed_utils};
+
+const DEFAULT_FEED_ENTRIES: u32 = 50;
+
+pub async fn get_activity_feed(
+    Query(query): Query<ActivityFeedQuery>,
+    State(db): State<Arc<Database>>
+) -> Result<Response, ApiError> {
+    // The default list order is newest first, which is what feed readers expect
+    let filter = ActivityListQuery {
+        team: query.team,
+        athlete_id: query.athlete_id,
+        sport_type: query.sport_type,
+        limit: Some(query.limit.unwrap_or(DEFAULT_FEED_ENTRIES)),
+        ..Default::default()
+    }.into_filter()?;
+    let page = filter.get_page(db.get_activity_page(&filter).await?);
+
+    let entries = feed_utils::get_activity_entries(&page.data);
+    let feed = AtomFeed {
+        id: "urn:bullsharks:feed:activities".to_string(),
+        title: "BullSharks activities".to_string(),
+        updated: feed_utils::get_feed_updated(&entries, Utc::now()),
+        entries,
+    };
+    Ok(feed_utils::atom_response(&feed))
+}
+
+pub async fn get_team_results_feed(
+    Query(query): Query<TeamResultsFeedQuery>,
+    State(db): State<Arc<Database>>,
+    State(controller): State<Arc<ActivityController>>
+) -> Result<Response, ApiError> {
+    let stats = match &query.competition_id {
+        Some(competition_id) => controller.get_competition_team_stats(competition_id).await?,
+        None => controller.get_current_team_stats().await?,
+    };
+    // Weeks are labelled by their Monday in the competition's timezone, like the stats themselves
+    let timezone = db.read_competition(&stats.competition_id).await?
+        .ok_or_else(|| ApiError::NotFound(format!("No competition found with id: {}", stats.competition_id)))?
+        .get_timezone()?;
+
+    let entries = feed_utils::get_team_week_entries(&stats, &timezone, Utc::now());
+    let feed = AtomFeed {
+        id: format!("urn:bullsharks:feed:team_results:{}", stats.competition_id),
+        title: "BullSharks weekly team results".to_string(),
+        updated: feed_utils::get_feed_updated(&entries, Utc::now()),
+        entries,
+    };
+    Ok(feed_utils::atom_response(&feed))
+}
@@ -0,0 +1,46 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::{Path, State}, http::{HeaderMap, StatusCode}};
+
+use crate::{error::ApiError, models::goal::{CreateGoal, Goal, GoalProgress}, services::goal_controller::GoalController, utils::auth_utils};
+
+pub async fn get_athlete_goals(
+    Path(id): Path<String>,
+    State(goal_controller): State<Arc<GoalController>>
+) -> Result<Json<Vec<GoalProgress>>, ApiError> {
+    let goals = goal_controller.get_goals(&id).await?;
+    Ok(Json(goals))
+}
+
+pub async fn get_athlete_goal(
+    Path((id, goal_id)): Path<(String, String)>,
+    State(goal_controller): State<Arc<GoalController>>
+) -> Result<Json<GoalProgress>, ApiError> {
+    let goal = goal_controller.get_goal(&id, &goal_id).await?;
+    Ok(Json(goal))
+}
+
+pub async fn create_athlete_goal(
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    State(goal_controller): State<Arc<GoalController>>,
+    Json(input): Json<CreateGoal>
+) -> Result<(StatusCode, Json<Goal>), ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    let goal = goal_controller.create_goal(&id, input).await?;
+    println!("[API] create_athlete_goal: Added {} {} goal '{}' for athlete '{}'", goal.period.as_str(), goal.metric.as_str(), goal.id, id);
+    Ok((StatusCode::CREATED, Json(goal)))
+}
+
+pub async fn archive_athlete_goal(
+    headers: HeaderMap,
+    Path((id, goal_id)): Path<(String, String)>,
+    State(goal_controller): State<Arc<GoalController>>
+) -> Result<StatusCode, ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    goal_controller.archive_goal(&id, &goal_id).await?;
+    println!("[API] archive_athlete_goal: Archived goal '{}' for athlete '{}'", goal_id, id);
+    Ok(StatusCode::NO_CONTENT)
+}
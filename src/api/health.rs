@@ -1,13 +1,14 @@
 use axum::{Json, extract::State};
 use serde::Serialize;
 
-use crate::utils::startup_utils::AppState;
+use crate::{models::rate_limit::RateLimitStatus, utils::startup_utils::AppState};
 
 #[derive(Serialize)]
 pub struct HealthStatus {
     pub database: String,
     pub strava: String,
     pub overall: String,
+    pub strava_rate_limit: Option<RateLimitStatus>,
 }
 
 pub async fn health_check(State(state): State<AppState>) -> Json<HealthStatus> {
@@ -31,5 +32,6 @@ pub async fn health_check(State(state): State<AppState>) -> Json<HealthStatus> {
         database: db_status,
         strava: strava_status,
         overall,
+        strava_rate_limit: state.activity_controller.get_strava_rate_limit(),
     })
 }
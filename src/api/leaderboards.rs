@@ -0,0 +1,13 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::{Query, State}};
+
+use crate::{error::ApiError, models::leaderboard::{Leaderboard, LeaderboardQuery}, services::leaderboard_controller::LeaderboardController};
+
+pub async fn get_leaderboard(
+    Query(query): Query<LeaderboardQuery>,
+    State(leaderboard_controller): State<Arc<LeaderboardController>>
+) -> Result<Json<Leaderboard>, ApiError> {
+    let leaderboard = leaderboard_controller.get_leaderboard(query).await?;
+    Ok(Json(leaderboard))
+}
@@ -0,0 +1,39 @@
+use std::{convert::Infallible, sync::Arc};
+
+use axum::{extract::State, response::sse::{Event, KeepAlive, Sse}};
+use futures_util::Stream;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::services::activity_controller::ActivityController;
+
+/*
+Each connection subscribes to the controller's broadcast channel, so browser tabs share the one
+update computed per change. A subscriber that falls too far behind gets a `resync` event instead of
+the updates it missed, and should refetch /team_stats.
+*/
+pub async fn get_live_updates(
+    State(controller): State<Arc<ActivityController>>
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    println!("[API] get_live_updates: Subscriber connected");
+    let receiver = controller.subscribe_live_updates();
+
+    let stream = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        let event = match receiver.recv().await {
+            Ok(update) => Event::default()
+                .event(update.kind.get_event_name())
+                .json_data(update.as_ref())
+                .unwrap_or_else(|e| {
+                    eprintln!("[API] get_live_updates: failed to serialize update: {}", e);
+                    Event::default().event("resync").data("{}")
+                }),
+            Err(RecvError::Lagged(missed)) => {
+                println!("[API] get_live_updates: Subscriber missed {} updates", missed);
+                Event::default().event("resync").data(format!("{{\"missed\":{}}}", missed))
+            }
+            Err(RecvError::Closed) => return None,
+        };
+        Some((Ok(event), receiver))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
@@ -1,3 +1,15 @@
 pub mod activities;
 pub mod health;
 pub mod athletes;
+pub mod auth;
+pub mod webhooks;
+pub mod competitions;
+pub mod teams;
+pub mod leaderboards;
+pub mod workouts;
+pub mod goals;
+pub mod events;
+pub mod exports;
+pub mod calendar;
+pub mod feeds;
+pub mod live;
@@ -0,0 +1,24 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::State, http::HeaderMap};
+
+use crate::{error::ApiError, models::team::Team, services::database::Database, utils::auth_utils};
+
+pub async fn get_teams(
+    State(db): State<Arc<Database>>
+) -> Result<Json<Vec<Team>>, ApiError> {
+    let teams = db.read_all_teams().await?;
+    Ok(Json(teams))
+}
+
+pub async fn upsert_team(
+    headers: HeaderMap,
+    State(db): State<Arc<Database>>,
+    Json(team): Json<Team>
+) -> Result<Json<Team>, ApiError> {
+    auth_utils::verify_admin_token(&headers)?;
+
+    let team = team.validate()?;
+    db.upsert_team(&team).await?;
+    Ok(Json(team))
+}
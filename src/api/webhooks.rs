@@ -0,0 +1,25 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::{Query, State}, http::StatusCode};
+
+use crate::{error::ApiError, models::webhook::{SubscriptionChallenge, SubscriptionChallengeResponse, WebhookEvent}, services::webhook_controller::WebhookController};
+
+pub async fn verify_strava_subscription(
+    Query(params): Query<SubscriptionChallenge>,
+    State(webhook_controller): State<Arc<WebhookController>>
+) -> Result<Json<SubscriptionChallengeResponse>, ApiError> {
+    webhook_controller.verify_challenge(&params)?;
+    println!("[API] verify_strava_subscription: Subscription challenge accepted");
+
+    Ok(Json(SubscriptionChallengeResponse {
+        challenge: params.challenge,
+    }))
+}
+
+pub async fn receive_strava_event(
+    State(webhook_controller): State<Arc<WebhookController>>,
+    Json(event): Json<WebhookEvent>
+) -> Result<StatusCode, ApiError> {
+    webhook_controller.enqueue(event)?;
+    Ok(StatusCode::OK)
+}
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use axum::{Json, extract::{Path, Query, State}};
+
+use crate::{error::ApiError, models::workout::{CompetitionWorkoutBreakdown, LongRunCount, RaceResult, WorkoutQuery}, services::workout_controller::WorkoutController};
+
+pub async fn get_race_results(
+    Query(query): Query<WorkoutQuery>,
+    State(workout_controller): State<Arc<WorkoutController>>
+) -> Result<Json<Vec<RaceResult>>, ApiError> {
+    let races = workout_controller.get_race_results(query).await?;
+    Ok(Json(races))
+}
+
+pub async fn get_long_run_counts(
+    Query(query): Query<WorkoutQuery>,
+    State(workout_controller): State<Arc<WorkoutController>>
+) -> Result<Json<Vec<LongRunCount>>, ApiError> {
+    let counts = workout_controller.get_long_run_counts(query).await?;
+    Ok(Json(counts))
+}
+
+pub async fn get_current_workout_breakdown(
+    State(workout_controller): State<Arc<WorkoutController>>
+) -> Result<Json<CompetitionWorkoutBreakdown>, ApiError> {
+    let breakdown = workout_controller.get_competition_breakdown(None).await?;
+    Ok(Json(breakdown))
+}
+
+pub async fn get_competition_workout_breakdown(
+    Path(id): Path<String>,
+    State(workout_controller): State<Arc<WorkoutController>>
+) -> Result<Json<CompetitionWorkoutBreakdown>, ApiError> {
+    let breakdown = workout_controller.get_competition_breakdown(Some(&id)).await?;
+    Ok(Json(breakdown))
+}
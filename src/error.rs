@@ -20,6 +20,8 @@ pub enum ApiError {
     ExternalAPIError(String),
     Unauthorized(String),
     BadRequest(String),
+    RateLimited(String),
+    NotFound(String),
 }
 
 /*
@@ -58,6 +60,14 @@ impl IntoResponse for ApiError {
                 StatusCode::BAD_REQUEST,
                 msg
             ),
+            ApiError::RateLimited(msg) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                msg
+            ),
+            ApiError::NotFound(msg) => (
+                StatusCode::NOT_FOUND,
+                msg
+            ),
         };
 
         let body = Json(json!({
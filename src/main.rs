@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use crate::utils::startup_utils;
+use crate::utils::startup_utils::{self, AppState};
 
 mod error;
 mod api;
@@ -13,8 +13,9 @@ async fn main() {
     dotenvy::dotenv().ok();
     let strava_config = startup_utils::get_strava_config();
     let db = startup_utils::get_db().await;
-    let auth_controller = startup_utils::get_auth_controller(strava_config.clone(), db.clone());
-    let strava_client = startup_utils::get_strava_client(auth_controller);
+    let strava_http = startup_utils::get_strava_http();
+    let auth_controller = startup_utils::get_auth_controller(strava_config.clone(), db.clone(), strava_http);
+    let strava_client = startup_utils::get_strava_client(auth_controller.clone());
 
     // Create ActivityController instead of starting scheduler
     let activity_controller = Arc::new(startup_utils::get_activity_controller(
@@ -22,6 +23,25 @@ async fn main() {
         strava_client
     ));
 
-    // Pass both db and activity_controller to the server
-    startup_utils::create_server(db, activity_controller).await;
+    // Webhook events are queued and processed in the background by the activity controller
+    let webhook_controller = startup_utils::get_webhook_controller(activity_controller.clone());
+
+    let leaderboard_controller = startup_utils::get_leaderboard_controller(db.clone());
+    let athlete_controller = startup_utils::get_athlete_controller(db.clone());
+    let workout_controller = startup_utils::get_workout_controller(db.clone());
+    let goal_controller = startup_utils::get_goal_controller(db.clone());
+    let event_controller = startup_utils::get_event_controller(db.clone());
+
+    // Pass db and the controllers to the server
+    startup_utils::create_server(AppState {
+        db,
+        activity_controller,
+        auth_controller,
+        webhook_controller,
+        leaderboard_controller,
+        athlete_controller,
+        workout_controller,
+        goal_controller,
+        event_controller,
+    }).await;
 }
@@ -1,9 +1,110 @@
 /* Internal */
 
-#[derive(serde::Deserialize, serde::Serialize, Debug)]
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{event::RaceEvent, team::Team};
+
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone)]
 pub struct Athlete {
     pub id: String,
     pub name: String,
     pub team: String,
-    pub event: String
-} 
\ No newline at end of file
+    /// Id of the event the athlete is training for
+    pub event: String,
+    pub strava_id: Option<String>,
+}
+
+/// Roster fields an admin can set, from a JSON body or a CSV row
+#[derive(Deserialize, Debug)]
+pub struct AthleteInput {
+    pub id: String,
+    pub name: String,
+    pub team: String,
+    pub event: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct UpdateAthlete {
+    pub name: String,
+    pub team: String,
+    pub event: String,
+}
+
+/// A stretch of time an athlete spent on a team. `valid_to` is None for the current team.
+#[derive(Serialize, Debug)]
+pub struct TeamMembership {
+    pub team: String,
+    pub valid_from: DateTime<Utc>,
+    pub valid_to: Option<DateTime<Utc>>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TransferRequest {
+    pub team: String,
+    /// When the athlete joins the new team, defaults to now
+    pub effective_at: Option<DateTime<Utc>>,
+}
+
+/// A raw activity name bound to a roster athlete
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AthleteAlias {
+    pub alias: String,
+    pub athlete_id: String,
+}
+
+/// An athlete name seen on activities that doesn't resolve to anyone on the roster
+#[derive(Serialize, Debug)]
+pub struct UnmatchedAthleteName {
+    pub name: String,
+    pub activity_count: i64,
+    pub last_seen: DateTime<FixedOffset>,
+    /// Ids of roster athletes the name could belong to, when it's ambiguous
+    pub candidates: Vec<String>,
+}
+
+/// Why a row of a roster upload was skipped. `row` is the line number in the file.
+#[derive(Serialize, Debug)]
+pub struct RosterRowError {
+    pub row: u64,
+    pub id: Option<String>,
+    pub error: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RosterUploadResult {
+    pub upserted: usize,
+    pub errors: Vec<RosterRowError>,
+}
+
+impl AthleteInput {
+    /// Teams and events are matched case-insensitively and stored by id
+    pub fn into_athlete(self, teams: &[Team], events: &[RaceEvent]) -> Result<Athlete, String> {
+        let id = self.id.trim().to_string();
+        if id.is_empty() {
+            return Err("Athlete id is required".to_string());
+        }
+        let name = self.name.trim().to_string();
+        if name.is_empty() {
+            return Err("Athlete name is required".to_string());
+        }
+
+        let team = teams
+            .iter()
+            .find(|team| team.id == Team::normalize_id(&self.team))
+            .ok_or_else(|| format!("Unknown team: {}", self.team))?;
+
+        let event = events
+            .iter()
+            .find(|event| event.id.eq_ignore_ascii_case(self.event.trim()))
+            .ok_or_else(|| format!("Unknown event: {}", self.event))?;
+
+        Ok(Athlete {
+            id,
+            name,
+            team: team.id.clone(),
+            event: event.id.clone(),
+            strava_id: None,
+        })
+    }
+}
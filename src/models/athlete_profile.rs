@@ -0,0 +1,67 @@
+/* Internal */
+
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{athlete::Athlete, bullshark::BullSharkActivity, effort::ActivityMetrics, streak::StreakSummary};
+
+#[derive(Deserialize, Debug)]
+pub struct AthleteProfileQuery {
+    /// Weeks of history to return, defaults to 12
+    pub weeks: Option<u32>,
+    /// Recent activities to return, defaults to 10
+    pub recent: Option<u32>,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct AthleteTotalsSummary {
+    pub activity_count: usize,
+    pub distance_km: f64,
+    pub moving_time_seconds: i64,
+    pub elevation_gain_meters: f64,
+    /// Moving time per kilometre across runs, None until the athlete has a run
+    pub average_pace_seconds_per_km: Option<f64>,
+    pub longest_run: Option<LongestRun>,
+    /// Pace, speed and effort across runs
+    pub run_metrics: ActivityMetrics,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LongestRun {
+    pub activity_id: String,
+    pub name: Option<String>,
+    pub date: DateTime<FixedOffset>,
+    pub distance_km: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AthleteWeek {
+    /// Monday 00:00 Pacific time
+    pub week_start: DateTime<Utc>,
+    pub activity_count: usize,
+    pub distance_km: f64,
+    pub moving_time_seconds: i64,
+    pub elevation_gain_meters: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SportBreakdown {
+    pub sport_type: String,
+    pub activity_count: usize,
+    pub distance_km: f64,
+    pub moving_time_seconds: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct AthleteProfile {
+    pub athlete: Athlete,
+    /// All-time totals
+    pub totals: AthleteTotalsSummary,
+    /// Oldest first, including weeks without activities
+    pub weekly: Vec<AthleteWeek>,
+    /// Most distance first
+    pub sports: Vec<SportBreakdown>,
+    /// Day and week running streaks, in Pacific time
+    pub streaks: StreakSummary,
+    pub recent_activities: Vec<BullSharkActivity>,
+}
@@ -4,11 +4,21 @@ These are internal models that we define.
 
 use chrono::{DateTime, FixedOffset};
 
+use crate::models::{effort::ActivityMetrics, workout::WorkoutCategory};
+
+/// Sport types that count as runs for pace and longest run
+pub const RUN_SPORT_TYPES: [&str; 3] = ["Run", "TrailRun", "VirtualRun"];
+
 #[derive(serde::Deserialize, serde::Serialize, Debug)]
 pub struct BullSharkActivity {
     pub id: String,
+    pub strava_activity_id: Option<i64>,
     pub date: DateTime<FixedOffset>,
+    pub first_seen_at: DateTime<FixedOffset>,
+    pub date_confidence: DateConfidence,
     pub athlete_name: Option<String>,
+    /// Roster athlete the name resolved to, if any
+    pub athlete_id: Option<String>,
     pub resource_state: Option<i64>,
     pub name: Option<String>,
     pub distance: Option<f64>,
@@ -17,5 +27,97 @@ pub struct BullSharkActivity {
     pub total_elevation_gain: Option<f64>,
     pub sport_type: Option<String>,
     pub workout_type: Option<i64>,
-    pub device_name: Option<String>
+    /// `workout_type` decoded, never stored
+    #[serde(default, skip_deserializing)]
+    pub workout_category: WorkoutCategory,
+    pub device_name: Option<String>,
+    /// Derived from the fields above, never stored
+    #[serde(default, skip_deserializing)]
+    pub metrics: ActivityMetrics,
+}
+
+impl BullSharkActivity {
+    pub fn is_run(&self) -> bool {
+        self.sport_type
+            .as_deref()
+            .is_some_and(|sport_type| RUN_SPORT_TYPES.contains(&sport_type))
+    }
+}
+
+/// An activity joined to the roster athlete who recorded it
+#[derive(Debug)]
+pub struct RosterActivity {
+    pub activity: BullSharkActivity,
+    pub athlete_name: String,
+    pub team: String,
+}
+
+/// How much we trust `BullSharkActivity.date`.
+/// The club feed has no start dates, so most dates are estimates.
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DateConfidence {
+    /// Start date read from the athlete's own activity feed
+    Exact,
+    /// Interpolated from the activity's position between two syncs
+    Estimated,
+    /// Only the time we first saw the activity is known
+    FirstSeen,
+}
+
+impl DateConfidence {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DateConfidence::Exact => "exact",
+            DateConfidence::Estimated => "estimated",
+            DateConfidence::FirstSeen => "first_seen",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "exact" => DateConfidence::Exact,
+            "estimated" => DateConfidence::Estimated,
+            _ => DateConfidence::FirstSeen,
+        }
+    }
+}
+
+/// Why an activity was soft-deleted
+#[derive(serde::Deserialize, serde::Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeletionReason {
+    /// Replaced by an edited version of the same activity
+    Edited,
+    /// Disappeared from the club feed without a replacement
+    Removed,
+    /// Deleted by the athlete, reported through a webhook
+    Deleted,
+}
+
+impl DeletionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeletionReason::Edited => "edited",
+            DeletionReason::Removed => "removed",
+            DeletionReason::Deleted => "deleted",
+        }
+    }
+}
+
+/// A stored activity the reconciliation pass decided to soft-delete
+#[derive(Debug)]
+pub struct ActivityDeletion {
+    pub id: String,
+    pub reason: DeletionReason,
+    pub superseded_by: Option<String>,
+}
+
+/// What `upsert_activity` changed
+#[derive(Debug)]
+pub struct ActivityUpsert {
+    /// A live row with the same id was updated rather than inserted
+    pub existed: bool,
+    /// Older versions that held the same Strava id and were superseded
+    pub replaced_ids: Vec<String>,
 }
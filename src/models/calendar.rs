@@ -0,0 +1,23 @@
+/* Internal */
+
+use serde::Deserialize;
+
+use crate::models::event::RaceEvent;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct CalendarQuery {
+    /// Activities recorded while on this team, and races this team's athletes are training for
+    pub team: Option<String>,
+    pub athlete_id: Option<String>,
+    pub sport_type: Option<String>,
+    /// RFC 3339, defaults to 90 days ago. Race events aren't limited by the window.
+    pub start: Option<String>,
+    pub end: Option<String>,
+}
+
+/// A scheduled race and the roster athletes training for it
+#[derive(Debug)]
+pub struct CalendarRace {
+    pub event: RaceEvent,
+    pub athlete_names: Vec<String>,
+}
@@ -0,0 +1,103 @@
+/* Internal */
+
+use chrono::{DateTime, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, models::{bullshark::BullSharkActivity, scoring::ScoringConfig, team::Team}};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Competition {
+    pub id: String,
+    pub name: String,
+    pub start_date: DateTime<Utc>,
+    /// None while the competition is ongoing
+    pub end_date: Option<DateTime<Utc>>,
+    /// IANA timezone used for weekly buckets, e.g. "America/Los_Angeles"
+    pub timezone: String,
+    pub teams: Vec<String>,
+    pub sport_types: Vec<String>,
+    #[serde(default)]
+    pub scoring: ScoringConfig,
+}
+
+impl Competition {
+    /// Why an activity doesn't count towards this competition, if it doesn't
+    pub fn get_ineligibility(&self, activity: &BullSharkActivity) -> Option<String> {
+        let (start_date, end_date) = self.get_window();
+        let activity_date = activity.date.with_timezone(&Utc);
+        if activity_date < start_date || activity_date > end_date {
+            return Some(format!("Activity date {} is outside the competition window", activity.date));
+        }
+        match &activity.sport_type {
+            Some(sport_type) if self.sport_types.contains(sport_type) => None,
+            Some(sport_type) => Some(format!("Sport type {} is not part of this competition", sport_type)),
+            None => Some("Activity has no sport type".to_string()),
+        }
+    }
+
+    pub fn get_timezone(&self) -> Result<Tz, ApiError> {
+        self.timezone
+            .parse::<Tz>()
+            .map_err(|_| ApiError::InternalConversionError(format!("Invalid competition timezone: {}", self.timezone)))
+    }
+
+    /// The window to read activities from, ending now for ongoing competitions
+    pub fn get_window(&self) -> (DateTime<Utc>, DateTime<Utc>) {
+        let end_date = match self.end_date {
+            Some(end_date) if end_date < Utc::now() => end_date,
+            _ => Utc::now(),
+        };
+        (self.start_date, end_date)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreateCompetition {
+    pub name: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: Option<DateTime<Utc>>,
+    pub timezone: Option<String>,
+    pub teams: Vec<String>,
+    pub sport_types: Option<Vec<String>>,
+    pub scoring: Option<ScoringConfig>,
+}
+
+impl CreateCompetition {
+    pub fn into_competition(self, id: String) -> Result<Competition, ApiError> {
+        if self.name.trim().is_empty() {
+            return Err(ApiError::BadRequest("Competition name is required".to_string()));
+        }
+        if let Some(end_date) = self.end_date
+            && end_date <= self.start_date {
+            return Err(ApiError::BadRequest("Competition end_date must be after start_date".to_string()));
+        }
+        if self.teams.is_empty() {
+            return Err(ApiError::BadRequest("Competition needs at least one team".to_string()));
+        }
+
+        let timezone = self.timezone.unwrap_or_else(|| "America/Los_Angeles".to_string());
+        if timezone.parse::<Tz>().is_err() {
+            return Err(ApiError::BadRequest(format!("Unknown timezone: {}", timezone)));
+        }
+
+        let sport_types = self.sport_types.unwrap_or_else(|| vec!["Run".to_string()]);
+        if sport_types.is_empty() {
+            return Err(ApiError::BadRequest("Competition needs at least one sport type".to_string()));
+        }
+
+        let scoring = self.scoring.unwrap_or_default();
+        scoring.validate()?;
+
+        Ok(Competition {
+            id,
+            name: self.name.trim().to_string(),
+            start_date: self.start_date,
+            end_date: self.end_date,
+            timezone,
+            teams: self.teams.iter().map(|team| Team::normalize_id(team)).collect(),
+            sport_types,
+            scoring,
+        })
+    }
+}
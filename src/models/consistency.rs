@@ -0,0 +1,49 @@
+/* Internal */
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::models::streak::StreakSummary;
+
+#[derive(Serialize, Debug)]
+pub struct AthleteConsistency {
+    pub athlete_id: String,
+    pub athlete_name: String,
+    /// The team the athlete is on now
+    pub team: String,
+    pub active_weeks: usize,
+    pub total_weeks: usize,
+    /// `active_weeks / total_weeks`, from 0 to 1
+    pub consistency: f64,
+    /// Streaks within the competition window
+    pub streaks: StreakSummary,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TeamParticipationWeek {
+    pub week_start: NaiveDate,
+    /// Athletes with a qualifying activity this week while on the team
+    pub active_athletes: usize,
+    /// Athletes on the team at any point during the week
+    pub roster_size: usize,
+    /// `active_athletes / roster_size`, from 0 to 1
+    pub participation_rate: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TeamParticipation {
+    pub team: String,
+    pub weeks: Vec<TeamParticipationWeek>,
+    /// Mean of the weekly participation rates
+    pub average_participation_rate: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CompetitionConsistency {
+    pub competition_id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Most consistent first
+    pub athletes: Vec<AthleteConsistency>,
+    pub teams: Vec<TeamParticipation>,
+}
@@ -0,0 +1,109 @@
+/* Internal */
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::bullshark::BullSharkActivity;
+
+const METERS_PER_MILE: f64 = 1609.344;
+// Naismith's rule: a metre of climbing costs about as much as 7.92 metres on the flat
+const FLAT_METERS_PER_METER_CLIMBED: f64 = 7.92;
+
+/// Values derived from distance, moving time, elapsed time and elevation gain.
+/// Each is None when the inputs it needs are missing or zero.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ActivityMetrics {
+    pub pace_seconds_per_km: Option<f64>,
+    pub pace_seconds_per_mile: Option<f64>,
+    pub average_speed_kmh: Option<f64>,
+    /// Share of elapsed time spent stopped, from 0 to 1
+    pub stopped_ratio: Option<f64>,
+    pub elevation_per_km: Option<f64>,
+    /// Pace on equivalent flat ground, counting each metre climbed as extra distance
+    pub grade_adjusted_pace_seconds_per_km: Option<f64>,
+}
+
+impl ActivityMetrics {
+    pub fn new(distance: Option<f64>, moving_time: Option<i64>, elapsed_time: Option<i64>, elevation_gain: Option<f64>) -> Self {
+        let distance = distance.filter(|d| *d > 0.0);
+        let moving_time = moving_time.filter(|t| *t > 0).map(|t| t as f64);
+        let elapsed_time = elapsed_time.filter(|t| *t > 0).map(|t| t as f64);
+        let kilometers = distance.map(|d| d / 1000.0);
+
+        let pace = |meters_per_unit: f64| match (distance, moving_time) {
+            (Some(distance), Some(moving_time)) => Some(moving_time / (distance / meters_per_unit)),
+            _ => None,
+        };
+
+        let grade_adjusted_pace_seconds_per_km = match (distance, moving_time) {
+            (Some(distance), Some(moving_time)) => {
+                let flat_distance = distance + elevation_gain.unwrap_or(0.0) * FLAT_METERS_PER_METER_CLIMBED;
+                Some(moving_time / (flat_distance / 1000.0))
+            }
+            _ => None,
+        };
+
+        ActivityMetrics {
+            pace_seconds_per_km: pace(1000.0),
+            pace_seconds_per_mile: pace(METERS_PER_MILE),
+            average_speed_kmh: match (kilometers, moving_time) {
+                (Some(kilometers), Some(moving_time)) => Some(kilometers / (moving_time / 3600.0)),
+                _ => None,
+            },
+            stopped_ratio: match (moving_time, elapsed_time) {
+                (Some(moving_time), Some(elapsed_time)) => Some(((elapsed_time - moving_time) / elapsed_time).clamp(0.0, 1.0)),
+                _ => None,
+            },
+            elevation_per_km: match (kilometers, elevation_gain) {
+                (Some(kilometers), Some(elevation_gain)) => Some(elevation_gain / kilometers),
+                _ => None,
+            },
+            grade_adjusted_pace_seconds_per_km,
+        }
+    }
+}
+
+/// Running sums used to derive metrics for a group of activities, e.g. an athlete or a team
+#[derive(Debug, Default, Clone)]
+pub struct EffortTotals {
+    pub distance: f64,
+    pub moving_time: i64,
+    pub elapsed_time: i64,
+    pub elevation_gain: f64,
+}
+
+impl EffortTotals {
+    pub fn add(&mut self, activity: &BullSharkActivity) {
+        self.distance += activity.distance.unwrap_or(0.0);
+        self.moving_time += activity.moving_time.unwrap_or(0);
+        self.elapsed_time += activity.elapsed_time.unwrap_or(0);
+        self.elevation_gain += activity.total_elevation_gain.unwrap_or(0.0);
+    }
+
+    pub fn get_metrics(&self) -> ActivityMetrics {
+        ActivityMetrics::new(Some(self.distance), Some(self.moving_time), Some(self.elapsed_time), Some(self.elevation_gain))
+    }
+}
+
+/// `EffortTotals` per sport type, since a pace averaged over runs and rides means nothing
+#[derive(Debug, Default, Clone)]
+pub struct SportEffortTotals {
+    sports: HashMap<String, EffortTotals>,
+}
+
+impl SportEffortTotals {
+    /// Activities without a sport type are left out
+    pub fn add(&mut self, activity: &BullSharkActivity) {
+        if let Some(sport_type) = &activity.sport_type {
+            self.sports.entry(sport_type.clone()).or_default().add(activity);
+        }
+    }
+
+    pub fn get_metrics(&self) -> HashMap<String, ActivityMetrics> {
+        self.sports
+            .iter()
+            .map(|(sport_type, totals)| (sport_type.clone(), totals.get_metrics()))
+            .collect()
+    }
+}
@@ -0,0 +1,111 @@
+/* Internal */
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApiError;
+
+/// A race athletes train for, referenced by `athletes.event`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RaceEvent {
+    /// Matched case-insensitively against roster `event` values
+    pub id: String,
+    pub name: String,
+    /// Race day in Pacific time, None for generic distances without a scheduled race
+    pub date: Option<NaiveDate>,
+    /// Metres
+    pub distance: Option<f64>,
+}
+
+impl RaceEvent {
+    pub fn validate(mut self) -> Result<Self, ApiError> {
+        self.id = self.id.trim().to_string();
+        if self.id.is_empty() {
+            return Err(ApiError::BadRequest("Event id is required".to_string()));
+        }
+        self.name = self.name.trim().to_string();
+        if self.name.is_empty() {
+            return Err(ApiError::BadRequest("Event name is required".to_string()));
+        }
+        if let Some(distance) = self.distance
+            && (!distance.is_finite() || distance <= 0.0) {
+            return Err(ApiError::BadRequest(format!("Event distance must be a positive number of metres, got {}", distance)));
+        }
+        Ok(self)
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct EventDashboardQuery {
+    /// Weeks of training history to return, defaults to 16
+    pub weeks: Option<u32>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrainingPhase {
+    /// The event has no race date
+    Unscheduled,
+    /// More than the taper window out
+    Build,
+    /// Within the final weeks before race week
+    Taper,
+    /// Race day is within the next 7 days
+    RaceWeek,
+    PostRace,
+}
+
+/// Whether the cohort has cut back from its peak volume
+#[derive(Serialize, Debug)]
+pub struct TaperIndicator {
+    pub phase: TrainingPhase,
+    /// Highest average weekly kilometres per athlete over the complete weeks shown
+    pub peak_week_km: f64,
+    pub peak_week_start: Option<NaiveDate>,
+    /// Average kilometres per athlete in the last complete week
+    pub last_week_km: f64,
+    /// 1 - last / peak, None until there is a peak
+    pub volume_drop: Option<f64>,
+    /// Volume has dropped by at least the taper threshold
+    pub tapering: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EventWeek {
+    /// Monday, Pacific time
+    pub week_start: NaiveDate,
+    /// Athletes with at least one run
+    pub active_athletes: usize,
+    pub total_km: f64,
+    /// Total over the whole cohort, including inactive athletes
+    pub average_km: f64,
+    /// Longest single run by anyone in the cohort
+    pub longest_run_km: f64,
+    /// Average of each active athlete's longest run
+    pub average_longest_run_km: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CohortAthlete {
+    pub athlete_id: String,
+    pub athlete_name: String,
+    pub team: String,
+    /// Aligned with the dashboard's `weeks`
+    pub weekly_km: Vec<f64>,
+    /// Longest run each week, aligned with the dashboard's `weeks`
+    pub weekly_longest_run_km: Vec<f64>,
+    /// Longest run over the weeks shown
+    pub longest_run_km: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EventDashboard {
+    pub event: RaceEvent,
+    /// Negative once the race is over, None without a race date
+    pub days_until_race: Option<i64>,
+    /// Oldest first, ending with race week or the current week, whichever comes first
+    pub weeks: Vec<EventWeek>,
+    pub cohort: Vec<CohortAthlete>,
+    pub taper: TaperIndicator,
+    pub generated_at: DateTime<Utc>,
+}
@@ -0,0 +1,113 @@
+/* Internal */
+
+use chrono::{DateTime, FixedOffset, NaiveDate};
+use serde::{Deserialize, Serialize};
+
+use crate::models::{bullshark::{BullSharkActivity, DateConfidence}, workout::WorkoutCategory};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn get_content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn get_extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ExportQuery {
+    /// `csv` or `ndjson`, wins over the Accept header
+    pub format: Option<String>,
+    /// RFC 3339. Activities default to all time, team stats to the competition's window.
+    pub start: Option<String>,
+    pub end: Option<String>,
+    /// Team stats only: whose window, timezone and sport types to use, defaults to the current competition
+    pub competition_id: Option<String>,
+    pub team: Option<String>,
+    pub athlete_id: Option<String>,
+    pub sport_type: Option<String>,
+}
+
+/// An activity and the team its athlete was on at the time
+#[derive(Serialize, Debug)]
+pub struct ActivityExport {
+    #[serde(flatten)]
+    pub activity: BullSharkActivity,
+    pub team: Option<String>,
+}
+
+/// `ActivityExport` flattened into spreadsheet columns
+#[derive(Serialize, Debug)]
+pub struct ActivityCsvRow {
+    pub id: String,
+    pub strava_activity_id: Option<i64>,
+    pub date: DateTime<FixedOffset>,
+    pub date_confidence: DateConfidence,
+    pub athlete_id: Option<String>,
+    pub athlete_name: Option<String>,
+    pub team: Option<String>,
+    pub name: Option<String>,
+    pub sport_type: Option<String>,
+    pub workout_type: Option<i64>,
+    pub workout_category: WorkoutCategory,
+    pub distance_meters: Option<f64>,
+    pub moving_time_seconds: Option<i64>,
+    pub elapsed_time_seconds: Option<i64>,
+    pub elevation_gain_meters: Option<f64>,
+    pub pace_seconds_per_km: Option<f64>,
+    pub grade_adjusted_pace_seconds_per_km: Option<f64>,
+    pub device_name: Option<String>,
+}
+
+impl From<ActivityExport> for ActivityCsvRow {
+    fn from(export: ActivityExport) -> Self {
+        let activity = export.activity;
+        ActivityCsvRow {
+            id: activity.id,
+            strava_activity_id: activity.strava_activity_id,
+            date: activity.date,
+            date_confidence: activity.date_confidence,
+            athlete_id: activity.athlete_id,
+            athlete_name: activity.athlete_name,
+            team: export.team,
+            name: activity.name,
+            sport_type: activity.sport_type,
+            workout_type: activity.workout_type,
+            workout_category: activity.workout_category,
+            distance_meters: activity.distance,
+            moving_time_seconds: activity.moving_time,
+            elapsed_time_seconds: activity.elapsed_time,
+            elevation_gain_meters: activity.total_elevation_gain,
+            pace_seconds_per_km: activity.metrics.pace_seconds_per_km,
+            grade_adjusted_pace_seconds_per_km: activity.metrics.grade_adjusted_pace_seconds_per_km,
+            device_name: activity.device_name,
+        }
+    }
+}
+
+/// One athlete's week for one team
+#[derive(Serialize, Debug)]
+pub struct TeamStatsExportRow {
+    pub team: String,
+    /// Monday, in the competition's timezone
+    pub week_start: NaiveDate,
+    pub athlete_id: String,
+    pub athlete_name: String,
+    pub activity_count: i64,
+    pub distance_km: f64,
+    pub moving_time_seconds: i64,
+    pub elevation_gain_meters: f64,
+}
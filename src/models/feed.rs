@@ -0,0 +1,41 @@
+/* Internal */
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+#[derive(Deserialize, Debug, Default)]
+pub struct ActivityFeedQuery {
+    /// The team the athlete was on when the activity happened
+    pub team: Option<String>,
+    pub athlete_id: Option<String>,
+    pub sport_type: Option<String>,
+    /// Entries to return, defaults to 50
+    pub limit: Option<u32>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct TeamResultsFeedQuery {
+    /// Defaults to the current competition
+    pub competition_id: Option<String>,
+}
+
+/// An Atom feed, rendered by `feed_utils::render_atom`
+#[derive(Debug)]
+pub struct AtomFeed {
+    /// A permanent IRI, e.g. "urn:bullsharks:feed:activities"
+    pub id: String,
+    pub title: String,
+    pub updated: DateTime<Utc>,
+    pub entries: Vec<AtomEntry>,
+}
+
+#[derive(Debug)]
+pub struct AtomEntry {
+    pub id: String,
+    pub title: String,
+    pub published: Option<DateTime<Utc>>,
+    pub updated: DateTime<Utc>,
+    /// Plain text, also used as the entry's content
+    pub summary: String,
+    pub link: Option<String>,
+}
@@ -0,0 +1,195 @@
+/* Internal */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, models::{bullshark::{BullSharkActivity, RUN_SPORT_TYPES}, competition::Competition}};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalMetric {
+    /// Kilometres
+    Distance,
+    /// Minutes of moving time
+    MovingTime,
+}
+
+impl GoalMetric {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalMetric::Distance => "distance",
+            GoalMetric::MovingTime => "moving_time",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "moving_time" => GoalMetric::MovingTime,
+            _ => GoalMetric::Distance,
+        }
+    }
+
+    /// How much an activity contributes, in the goal's unit
+    pub fn get_value(&self, activity: &BullSharkActivity) -> f64 {
+        match self {
+            GoalMetric::Distance => activity.distance.unwrap_or(0.0) / 1000.0,
+            GoalMetric::MovingTime => activity.moving_time.unwrap_or(0) as f64 / 60.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalPeriod {
+    /// Every Monday to Sunday, Pacific time
+    Week,
+    /// Every calendar month, Pacific time
+    Month,
+    /// Once, over a single competition
+    Competition,
+}
+
+impl GoalPeriod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GoalPeriod::Week => "week",
+            GoalPeriod::Month => "month",
+            GoalPeriod::Competition => "competition",
+        }
+    }
+
+    pub fn from_db(value: &str) -> Self {
+        match value {
+            "month" => GoalPeriod::Month,
+            "competition" => GoalPeriod::Competition,
+            _ => GoalPeriod::Week,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Goal {
+    pub id: String,
+    pub athlete_id: String,
+    pub metric: GoalMetric,
+    pub period: GoalPeriod,
+    /// Kilometres or minutes, depending on the metric
+    pub target: f64,
+    /// Set for competition goals only
+    pub competition_id: Option<String>,
+    /// Sport types that count towards the goal
+    pub sport_types: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    /// Archived goals keep their history but stop tracking new periods
+    pub archived_at: Option<DateTime<Utc>>,
+}
+
+impl Goal {
+    pub fn is_active(&self) -> bool {
+        self.archived_at.is_none()
+    }
+
+    pub fn counts(&self, activity: &BullSharkActivity) -> bool {
+        activity.sport_type.as_ref().is_some_and(|sport_type| self.sport_types.contains(sport_type))
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreateGoal {
+    pub metric: GoalMetric,
+    pub period: GoalPeriod,
+    pub target: f64,
+    /// Required for competition goals
+    pub competition_id: Option<String>,
+    /// Defaults to the competition's sport types for competition goals, and runs otherwise
+    pub sport_types: Option<Vec<String>>,
+}
+
+impl CreateGoal {
+    pub fn into_goal(self, id: String, athlete_id: &str, competition: Option<&Competition>) -> Result<Goal, ApiError> {
+        if !self.target.is_finite() || self.target <= 0.0 {
+            return Err(ApiError::BadRequest("Goal target must be a positive number".to_string()));
+        }
+
+        let competition_id = match (self.period, competition) {
+            (GoalPeriod::Competition, Some(competition)) => Some(competition.id.clone()),
+            (GoalPeriod::Competition, None) => {
+                return Err(ApiError::BadRequest("Competition goals need a competition_id".to_string()));
+            }
+            (_, Some(_)) => {
+                return Err(ApiError::BadRequest("Only competition goals can have a competition_id".to_string()));
+            }
+            (_, None) => None,
+        };
+
+        let sport_types = match (self.sport_types, competition) {
+            (Some(sport_types), _) => sport_types,
+            (None, Some(competition)) => competition.sport_types.clone(),
+            (None, None) => RUN_SPORT_TYPES.iter().map(|sport| sport.to_string()).collect(),
+        };
+        if sport_types.is_empty() {
+            return Err(ApiError::BadRequest("Goal needs at least one sport type".to_string()));
+        }
+
+        Ok(Goal {
+            id,
+            athlete_id: athlete_id.to_string(),
+            metric: self.metric,
+            period: self.period,
+            target: self.target,
+            competition_id,
+            sport_types,
+            created_at: Utc::now(),
+            archived_at: None,
+        })
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalStatus {
+    /// The period is still running and the target hasn't been reached
+    InProgress,
+    Hit,
+    Missed,
+}
+
+/// How a goal went over one period
+#[derive(Serialize, Debug)]
+pub struct GoalPeriodResult {
+    pub start: DateTime<Utc>,
+    /// None for competitions without an end date
+    pub end: Option<DateTime<Utc>>,
+    pub value: f64,
+    pub target: f64,
+    /// 0.0 to 1.0 and beyond, once the target is passed
+    pub fraction_complete: f64,
+    pub status: GoalStatus,
+    /// When the activity that reached the target started
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// The period in progress, with a projection from the rate of accumulation so far
+#[derive(Serialize, Debug)]
+pub struct GoalProjection {
+    pub remaining: f64,
+    /// Share of the period that has passed, None for competitions without an end date
+    pub elapsed_fraction: Option<f64>,
+    /// Where the athlete ends up at the current rate, None for competitions without an end date
+    pub projected_value: Option<f64>,
+    /// When the target is reached at the current rate, None once hit or if nothing has been logged yet
+    pub projected_completion: Option<DateTime<Utc>>,
+    pub on_track: bool,
+}
+
+#[derive(Serialize, Debug)]
+pub struct GoalProgress {
+    pub goal: Goal,
+    /// None once archived, or before a competition goal's competition starts
+    pub current: Option<GoalPeriodResult>,
+    pub projection: Option<GoalProjection>,
+    /// Finished periods since the goal was created, newest first
+    pub history: Vec<GoalPeriodResult>,
+    pub hit_count: usize,
+    pub missed_count: usize,
+}
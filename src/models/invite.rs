@@ -0,0 +1,38 @@
+/* Internal */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A one-time link for a roster athlete to connect their Strava account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StravaInvite {
+    pub token: String,
+    pub athlete_id: String,
+    /// Allows overwriting a Strava account the athlete is already linked to
+    pub replace_strava_id: bool,
+    pub expires_at: DateTime<Utc>,
+    pub used_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateStravaInvite {
+    pub athlete_id: String,
+    #[serde(default)]
+    pub replace_strava_id: bool,
+}
+
+/// Returned to the admin, who sends `login_path` to the athlete
+#[derive(Debug, Serialize)]
+pub struct StravaInviteLink {
+    #[serde(flatten)]
+    pub invite: StravaInvite,
+    pub login_path: String,
+}
+
+/// Returned to the athlete once they've connected their Strava account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StravaConnection {
+    pub athlete_id: String,
+    pub strava_id: String,
+    pub scope: String,
+}
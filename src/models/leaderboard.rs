@@ -0,0 +1,117 @@
+/* Internal */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::models::effort::ActivityMetrics;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardMetric {
+    /// Kilometres
+    #[default]
+    Distance,
+    /// Seconds
+    MovingTime,
+    /// Metres
+    ElevationGain,
+    ActivityCount,
+    /// Kilometres of the athlete's longest single activity
+    LongestRun,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LeaderboardPeriod {
+    /// Monday to Sunday, Pacific time
+    #[default]
+    Week,
+    Month,
+    Competition,
+    Custom,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LeaderboardQuery {
+    #[serde(default)]
+    pub metric: LeaderboardMetric,
+    #[serde(default)]
+    pub period: LeaderboardPeriod,
+    /// For the competition period, defaults to the current competition
+    pub competition_id: Option<String>,
+    /// RFC 3339, required for the custom period
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub team: Option<String>,
+    pub sport_type: Option<String>,
+}
+
+/// An athlete's raw totals over a window
+#[derive(Debug)]
+pub struct AthleteTotals {
+    pub athlete_id: String,
+    pub athlete_name: String,
+    pub team: String,
+    /// Metres
+    pub distance: f64,
+    /// Seconds
+    pub moving_time: i64,
+    /// Seconds
+    pub elapsed_time: i64,
+    /// Metres
+    pub elevation_gain: f64,
+    pub activity_count: i64,
+    /// Metres
+    pub longest_distance: f64,
+}
+
+impl AthleteTotals {
+    pub fn get_value(&self, metric: LeaderboardMetric) -> f64 {
+        match metric {
+            LeaderboardMetric::Distance => self.distance / 1000.0,
+            LeaderboardMetric::MovingTime => self.moving_time as f64,
+            LeaderboardMetric::ElevationGain => self.elevation_gain,
+            LeaderboardMetric::ActivityCount => self.activity_count as f64,
+            LeaderboardMetric::LongestRun => self.longest_distance / 1000.0,
+        }
+    }
+
+    pub fn get_metrics(&self) -> ActivityMetrics {
+        ActivityMetrics::new(Some(self.distance), Some(self.moving_time), Some(self.elapsed_time), Some(self.elevation_gain))
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct LeaderboardEntry {
+    /// Tied athletes share a rank and the next rank is skipped, e.g. 1, 2, 2, 4
+    pub rank: usize,
+    pub athlete_id: String,
+    pub athlete_name: String,
+    pub team: String,
+    pub value: f64,
+    pub activity_count: i64,
+    /// Pace, speed and effort across the athlete's activities in the window.
+    /// None unless the leaderboard covers a single sport type, since paces across sports don't average.
+    pub metrics: Option<ActivityMetrics>,
+    /// None if the athlete had no activities in the previous period
+    pub previous_rank: Option<usize>,
+    pub previous_value: Option<f64>,
+    /// `value` minus `previous_value`, treating a missing previous value as 0
+    pub delta: f64,
+    /// Places gained since the previous period, negative if the athlete dropped
+    pub rank_change: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Leaderboard {
+    pub metric: LeaderboardMetric,
+    pub period: LeaderboardPeriod,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub previous_start: DateTime<Utc>,
+    pub previous_end: DateTime<Utc>,
+    pub team: Option<String>,
+    /// None means every sport type counts
+    pub sport_types: Option<Vec<String>>,
+    pub entries: Vec<LeaderboardEntry>,
+}
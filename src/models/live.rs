@@ -0,0 +1,88 @@
+/* Internal */
+
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::models::{bullshark::BullSharkActivity, team_stats::MultiTeamStats};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LiveUpdateKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl LiveUpdateKind {
+    /// The SSE event name; new activities keep the original `activities` event
+    pub fn get_event_name(&self) -> &'static str {
+        match self {
+            LiveUpdateKind::Created => "activities",
+            LiveUpdateKind::Updated => "activities_updated",
+            LiveUpdateKind::Deleted => "activities_deleted",
+        }
+    }
+}
+
+/// Pushed to every live subscriber when activities are stored, edited or deleted
+#[derive(Serialize, Debug)]
+pub struct LiveUpdate {
+    #[serde(skip)]
+    pub kind: LiveUpdateKind,
+    /// New activities, or the current version of edited ones. Empty for deletions.
+    pub activities: Vec<BullSharkActivity>,
+    /// Ids clients should drop: versions an edit replaced, or deleted activities
+    pub removed_ids: Vec<String>,
+    /// Current competition totals after the change, None when no competition has started
+    pub team_totals: Option<LiveTeamTotals>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl LiveUpdate {
+    pub fn new(kind: LiveUpdateKind, activities: Vec<BullSharkActivity>, removed_ids: Vec<String>) -> Self {
+        LiveUpdate {
+            kind,
+            activities,
+            removed_ids,
+            team_totals: None,
+            generated_at: Utc::now(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.activities.is_empty() && self.removed_ids.is_empty()
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LiveTeamTotals {
+    pub competition_id: String,
+    /// Keyed by team id
+    pub teams: HashMap<String, LiveTeamTotal>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LiveTeamTotal {
+    pub display_name: String,
+    pub color: Option<String>,
+    pub total_kilometers: f64,
+    pub total_points: f64,
+}
+
+impl From<MultiTeamStats> for LiveTeamTotals {
+    fn from(stats: MultiTeamStats) -> Self {
+        LiveTeamTotals {
+            competition_id: stats.competition_id,
+            teams: stats.teams
+                .into_iter()
+                .map(|(team_id, team)| (team_id, LiveTeamTotal {
+                    display_name: team.display_name,
+                    color: team.color,
+                    total_kilometers: team.total_kilometers,
+                    total_points: team.total_points,
+                }))
+                .collect(),
+        }
+    }
+}
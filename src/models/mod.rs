@@ -3,3 +3,23 @@ pub mod club;
 pub mod bullshark;
 pub mod athlete;
 pub mod team_stats;
+pub mod rate_limit;
+pub mod strava_activity;
+pub mod webhook;
+pub mod competition;
+pub mod team;
+pub mod scoring;
+pub mod leaderboard;
+pub mod athlete_profile;
+pub mod effort;
+pub mod workout;
+pub mod streak;
+pub mod consistency;
+pub mod goal;
+pub mod event;
+pub mod pagination;
+pub mod export;
+pub mod calendar;
+pub mod feed;
+pub mod live;
+pub mod invite;
@@ -48,4 +48,13 @@ pub struct StravaTokenResponse {
     pub expires_at: i64,
     pub expires_in: i32,
     pub refresh_token: String,
-}
\ No newline at end of file
+    /// Only present on the initial authorization code exchange
+    pub athlete: Option<StravaTokenAthlete>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StravaTokenAthlete {
+    pub id: i64,
+    pub firstname: Option<String>,
+    pub lastname: Option<String>,
+}
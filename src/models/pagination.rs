@@ -0,0 +1,310 @@
+/* Internal */
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, models::{bullshark::BullSharkActivity, team::Team, workout::WorkoutCategory}, utils::time_utils};
+
+const DEFAULT_PAGE_SIZE: u32 = 100;
+const MAX_PAGE_SIZE: u32 = 1000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivitySort {
+    #[default]
+    Date,
+    Distance,
+    MovingTime,
+    ElevationGain,
+}
+
+impl ActivitySort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivitySort::Date => "date",
+            ActivitySort::Distance => "distance",
+            ActivitySort::MovingTime => "moving_time",
+            ActivitySort::ElevationGain => "elevation_gain",
+        }
+    }
+
+    /// The SQL expression to order by and the type the cursor value is cast to.
+    /// Missing values sort as zero so keyset comparisons never meet a NULL.
+    pub fn get_sql(&self) -> (&'static str, &'static str) {
+        match self {
+            ActivitySort::Date => ("b.date", "timestamptz"),
+            ActivitySort::Distance => ("COALESCE(b.distance, 0)", "float8"),
+            ActivitySort::MovingTime => ("COALESCE(b.moving_time, 0)", "bigint"),
+            ActivitySort::ElevationGain => ("COALESCE(b.total_elevation_gain, 0)", "float8"),
+        }
+    }
+
+    /// The activity's sort value in the form the cursor stores it
+    pub fn get_value(&self, activity: &BullSharkActivity) -> String {
+        match self {
+            ActivitySort::Date => activity.date.with_timezone(&Utc).to_rfc3339(),
+            ActivitySort::Distance => activity.distance.unwrap_or(0.0).to_string(),
+            ActivitySort::MovingTime => activity.moving_time.unwrap_or(0).to_string(),
+            ActivitySort::ElevationGain => activity.total_elevation_gain.unwrap_or(0.0).to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    #[default]
+    Desc,
+    Asc,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Desc => "desc",
+            SortOrder::Asc => "asc",
+        }
+    }
+}
+
+/// Filters, sort and paging accepted by every activity list route
+#[derive(Deserialize, Debug, Default)]
+pub struct ActivityListQuery {
+    pub athlete_id: Option<String>,
+    /// The team the athlete was on when the activity happened
+    pub team: Option<String>,
+    pub sport_type: Option<String>,
+    pub workout_type: Option<WorkoutCategory>,
+    /// Metres
+    pub min_distance: Option<f64>,
+    pub max_distance: Option<f64>,
+    /// RFC 3339
+    pub start: Option<String>,
+    pub end: Option<String>,
+    #[serde(default)]
+    pub sort: ActivitySort,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub limit: Option<u32>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+}
+
+/// Position after the last activity of a page: its sort value and id
+#[derive(Debug, Clone)]
+pub struct ActivityCursor {
+    pub value: String,
+    pub id: String,
+}
+
+/// A validated `ActivityListQuery`, ready to run
+#[derive(Debug, Clone)]
+pub struct ActivityFilter {
+    pub athlete_id: Option<String>,
+    pub team: Option<String>,
+    pub sport_type: Option<String>,
+    pub workout_types: Option<Vec<i64>>,
+    pub min_distance: Option<f64>,
+    pub max_distance: Option<f64>,
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub sort: ActivitySort,
+    pub order: SortOrder,
+    /// None when the client asked for no paging, in which case every matching activity is returned
+    pub limit: Option<u32>,
+    pub cursor: Option<ActivityCursor>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Page<T> {
+    pub data: Vec<T>,
+    /// Pass as `cursor` to fetch the next page, None on the last page
+    pub next_cursor: Option<String>,
+    pub limit: u32,
+}
+
+/// Requests without `limit` or `cursor` keep the original bare array, so existing clients see no change.
+/// Every paged request gets a `Page`, so `next_cursor` is consistent across the list routes.
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum ActivityList<T> {
+    All(Vec<T>),
+    Page(Page<T>),
+}
+
+impl ActivityListQuery {
+    pub fn into_filter(self) -> Result<ActivityFilter, ApiError> {
+        for (field, distance) in [("min_distance", self.min_distance), ("max_distance", self.max_distance)] {
+            if let Some(distance) = distance
+                && (!distance.is_finite() || distance < 0.0) {
+                return Err(ApiError::BadRequest(format!("{} must be a non-negative number of metres", field)));
+            }
+        }
+        if let (Some(min_distance), Some(max_distance)) = (self.min_distance, self.max_distance)
+            && min_distance > max_distance {
+            return Err(ApiError::BadRequest("min_distance can't be greater than max_distance".to_string()));
+        }
+
+        let start = self.start.as_deref().map(|start| time_utils::parse_datetime("start", start)).transpose()?;
+        let end = self.end.as_deref().map(|end| time_utils::parse_datetime("end", end)).transpose()?;
+        if let (Some(start), Some(end)) = (start, end)
+            && end < start {
+            return Err(ApiError::BadRequest("end must be after start".to_string()));
+        }
+
+        // A cursor on its own continues paging at the default size
+        let limit = match self.limit {
+            Some(limit) if limit == 0 || limit > MAX_PAGE_SIZE => {
+                return Err(ApiError::BadRequest(format!("limit must be between 1 and {}", MAX_PAGE_SIZE)));
+            }
+            Some(limit) => Some(limit),
+            None if self.cursor.is_some() => Some(DEFAULT_PAGE_SIZE),
+            None => None,
+        };
+
+        let cursor = self.cursor
+            .as_deref()
+            .map(|cursor| ActivityCursor::decode(cursor, self.sort, self.order))
+            .transpose()?;
+
+        Ok(ActivityFilter {
+            athlete_id: self.athlete_id,
+            team: self.team.as_deref().map(Team::normalize_id),
+            sport_type: self.sport_type,
+            workout_types: self.workout_type.map(|category| category.get_strava_codes().to_vec()),
+            min_distance: self.min_distance,
+            max_distance: self.max_distance,
+            start,
+            end,
+            sort: self.sort,
+            order: self.order,
+            limit,
+            cursor,
+        })
+    }
+}
+
+impl ActivityFilter {
+    /// Narrows the date range to a route's fixed window
+    pub fn within(mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        self.start = Some(self.start.map_or(start, |own| own.max(start)));
+        self.end = Some(self.end.map_or(end, |own| own.min(end)));
+        self
+    }
+
+    /// Every activity when the filter isn't paged, otherwise a page
+    pub fn get_list(&self, activities: Vec<BullSharkActivity>) -> ActivityList<BullSharkActivity> {
+        match self.limit {
+            Some(_) => ActivityList::Page(self.get_page(activities)),
+            None => ActivityList::All(activities),
+        }
+    }
+
+    /// Builds the page from up to `limit + 1` rows; the extra row only signals that there is a next page
+    pub fn get_page(&self, mut activities: Vec<BullSharkActivity>) -> Page<BullSharkActivity> {
+        let limit = self.limit.unwrap_or(activities.len() as u32);
+        let has_more = activities.len() > limit as usize;
+        activities.truncate(limit as usize);
+
+        let next_cursor = activities
+            .last()
+            .filter(|_| has_more)
+            .map(|last| ActivityCursor {
+                value: self.sort.get_value(last),
+                id: last.id.clone(),
+            }.encode(self.sort, self.order));
+
+        Page {
+            data: activities,
+            next_cursor,
+            limit,
+        }
+    }
+}
+
+/*
+Cursors are opaque to clients: "sort:order:value:id" hex-encoded so they survive a query string untouched.
+The sort and order are kept so a cursor can't be replayed against a different ordering.
+*/
+impl ActivityCursor {
+    pub fn encode(&self, sort: ActivitySort, order: SortOrder) -> String {
+        hex::encode(format!("{}:{}:{}:{}", sort.as_str(), order.as_str(), self.value, self.id))
+    }
+
+    pub fn decode(cursor: &str, sort: ActivitySort, order: SortOrder) -> Result<Self, ApiError> {
+        let invalid = || ApiError::BadRequest("Invalid cursor".to_string());
+        let bytes = hex::decode(cursor).map_err(|_| invalid())?;
+        let decoded = String::from_utf8(bytes).map_err(|_| invalid())?;
+
+        // Ids and values never contain ':' except RFC 3339 offsets, so split the id off the end
+        let (prefix, id) = decoded.rsplit_once(':').ok_or_else(invalid)?;
+        let mut parts = prefix.splitn(3, ':');
+        let (Some(cursor_sort), Some(cursor_order), Some(value)) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(invalid());
+        };
+        if cursor_sort != sort.as_str() || cursor_order != order.as_str() {
+            return Err(ApiError::BadRequest("Cursor was issued for a different sort or order".to_string()));
+        }
+
+        let valid_value = match sort {
+            ActivitySort::Date => DateTime::parse_from_rfc3339(value).is_ok(),
+            ActivitySort::MovingTime => value.parse::<i64>().is_ok(),
+            ActivitySort::Distance | ActivitySort::ElevationGain => value.parse::<f64>().is_ok_and(f64::is_finite),
+        };
+        if !valid_value || id.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(ActivityCursor {
+            value: value.to_string(),
+            id: id.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_message(error: ApiError) -> String {
+        match error {
+            ApiError::BadRequest(message) => message,
+            other => panic!("expected BadRequest, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn cursor_round_trips() {
+        let cursor = ActivityCursor {
+            value: "2026-03-01T08:30:00+00:00".to_string(),
+            id: "abc123".to_string(),
+        };
+        let encoded = cursor.encode(ActivitySort::Date, SortOrder::Desc);
+
+        let decoded = ActivityCursor::decode(&encoded, ActivitySort::Date, SortOrder::Desc).unwrap();
+        assert_eq!(decoded.value, cursor.value);
+        assert_eq!(decoded.id, cursor.id);
+    }
+
+    #[test]
+    fn malformed_cursor_is_rejected() {
+        for cursor in ["", "abc", "zz", &hex::encode("distance:asc"), &hex::encode("distance:asc:far:abc123"), &hex::encode("distance:asc:12.5:")] {
+            let error = ActivityCursor::decode(cursor, ActivitySort::Distance, SortOrder::Asc).unwrap_err();
+            assert_eq!(get_message(error), "Invalid cursor", "cursor {:?}", cursor);
+        }
+    }
+
+    #[test]
+    fn cursor_from_another_ordering_is_rejected() {
+        let cursor = ActivityCursor {
+            value: "1200".to_string(),
+            id: "abc123".to_string(),
+        };
+
+        for (sort, order) in [(ActivitySort::Distance, SortOrder::Desc), (ActivitySort::MovingTime, SortOrder::Asc)] {
+            let encoded = cursor.encode(sort, order);
+            let error = ActivityCursor::decode(&encoded, ActivitySort::MovingTime, SortOrder::Desc).unwrap_err();
+            assert_eq!(get_message(error), "Cursor was issued for a different sort or order");
+        }
+    }
+}
@@ -0,0 +1,109 @@
+/*
+Internal snapshot of Strava's rate limit headers.
+Strava reports two windows: a rolling 15 minute budget and a daily budget.
+*/
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RateLimitStatus {
+    #[serde(rename = "shortTermLimit")]
+    pub short_term_limit: u32,
+    #[serde(rename = "shortTermUsage")]
+    pub short_term_usage: u32,
+    #[serde(rename = "dailyLimit")]
+    pub daily_limit: u32,
+    #[serde(rename = "dailyUsage")]
+    pub daily_usage: u32,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RateLimitStatus {
+    /// Parse Strava's `X-RateLimit-Limit` and `X-RateLimit-Usage` values, e.g. "200,2000" and "34,342"
+    pub fn from_headers(limit: &str, usage: &str) -> Option<Self> {
+        let (short_term_limit, daily_limit) = Self::parse_pair(limit)?;
+        let (short_term_usage, daily_usage) = Self::parse_pair(usage)?;
+        Some(RateLimitStatus {
+            short_term_limit,
+            short_term_usage,
+            daily_limit,
+            daily_usage,
+            updated_at: Utc::now(),
+        })
+    }
+
+    /// Check if either budget is used up. The short term window resets every quarter hour,
+    /// so a stale snapshot from a previous window is ignored.
+    pub fn is_exhausted(&self) -> bool {
+        self.is_exhausted_at(Utc::now())
+    }
+
+    fn is_exhausted_at(&self, now: DateTime<Utc>) -> bool {
+        let same_window = now.timestamp() / 900 == self.updated_at.timestamp() / 900;
+        let same_day = now.date_naive() == self.updated_at.date_naive();
+        (same_window && self.short_term_usage >= self.short_term_limit)
+            || (same_day && self.daily_usage >= self.daily_limit)
+    }
+
+    fn parse_pair(value: &str) -> Option<(u32, u32)> {
+        let mut parts = value.split(',').map(|p| p.trim().parse::<u32>());
+        let first = parts.next()?.ok()?;
+        let second = parts.next()?.ok()?;
+        Some((first, second))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn get_status(usage: &str, updated_at: DateTime<Utc>) -> RateLimitStatus {
+        RateLimitStatus {
+            updated_at,
+            ..RateLimitStatus::from_headers("200,2000", usage).unwrap()
+        }
+    }
+
+    #[test]
+    fn parses_limit_and_usage_pairs() {
+        let status = RateLimitStatus::from_headers("200,2000", " 34, 342").unwrap();
+        assert_eq!((status.short_term_limit, status.daily_limit), (200, 2000));
+        assert_eq!((status.short_term_usage, status.daily_usage), (34, 342));
+    }
+
+    #[test]
+    fn rejects_malformed_or_missing_pairs() {
+        for (limit, usage) in [("", "34,342"), ("200", "34,342"), ("200,", "34,342"), ("200,2000", "34"), ("200,abc", "34,342"), ("200,2000", "-1,342")] {
+            assert!(RateLimitStatus::from_headers(limit, usage).is_none(), "{:?} {:?}", limit, usage);
+        }
+    }
+
+    #[test]
+    fn short_term_budget_resets_at_the_quarter_hour() {
+        let updated_at = Utc.with_ymd_and_hms(2026, 3, 1, 10, 14, 0).unwrap();
+        let status = get_status("200,500", updated_at);
+
+        assert!(status.is_exhausted_at(Utc.with_ymd_and_hms(2026, 3, 1, 10, 14, 59).unwrap()));
+        assert!(!status.is_exhausted_at(Utc.with_ymd_and_hms(2026, 3, 1, 10, 15, 0).unwrap()));
+    }
+
+    #[test]
+    fn daily_budget_resets_at_midnight_utc() {
+        let updated_at = Utc.with_ymd_and_hms(2026, 3, 1, 23, 50, 0).unwrap();
+        let status = get_status("12,2000", updated_at);
+
+        // Still exhausted in a later quarter hour of the same day
+        assert!(status.is_exhausted_at(Utc.with_ymd_and_hms(2026, 3, 1, 23, 59, 59).unwrap()));
+        assert!(!status.is_exhausted_at(Utc.with_ymd_and_hms(2026, 3, 2, 0, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn under_both_budgets_is_not_exhausted() {
+        let updated_at = Utc.with_ymd_and_hms(2026, 3, 1, 10, 0, 0).unwrap();
+        assert!(!get_status("199,1999", updated_at).is_exhausted_at(updated_at));
+    }
+}
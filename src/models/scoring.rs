@@ -0,0 +1,146 @@
+/* Internal */
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::ApiError, models::bullshark::BullSharkActivity};
+
+/// What a competition awards points for
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScoringRule {
+    /// One point per kilometre
+    Distance,
+    /// One point per minute of moving time
+    MovingTime,
+    /// One point per metre of elevation gain
+    ElevationGain,
+    /// One point per activity
+    ActivityCount,
+    /// A weighted sum of the above
+    Weighted {
+        #[serde(default)]
+        per_km: f64,
+        #[serde(default)]
+        per_minute: f64,
+        #[serde(default)]
+        per_meter_elevation: f64,
+        #[serde(default)]
+        per_activity: f64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ScoringConfig {
+    pub rule: ScoringRule,
+    /// Multiplier per Strava sport type, e.g. {"Ride": 0.25, "Walk": 0.5}. Unlisted sports use 1.0.
+    #[serde(default)]
+    pub sport_multipliers: HashMap<String, f64>,
+}
+
+impl Default for ScoringConfig {
+    fn default() -> Self {
+        ScoringConfig {
+            rule: ScoringRule::Distance,
+            sport_multipliers: HashMap::new(),
+        }
+    }
+}
+
+/// One line of a points explanation, e.g. "distance: 10.2 km x 1.0 = 10.2"
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PointsComponent {
+    pub metric: String,
+    pub value: f64,
+    pub weight: f64,
+    pub points: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PointsBreakdown {
+    pub activity_id: String,
+    pub sport_type: Option<String>,
+    pub eligible: bool,
+    /// Why the activity scores nothing, when it isn't eligible
+    pub reason: Option<String>,
+    pub components: Vec<PointsComponent>,
+    pub base_points: f64,
+    pub sport_multiplier: f64,
+    pub points: f64,
+}
+
+impl ScoringConfig {
+    pub fn validate(&self) -> Result<(), ApiError> {
+        if let Some((sport, multiplier)) = self.sport_multipliers.iter().find(|(_, m)| !m.is_finite() || **m < 0.0) {
+            return Err(ApiError::BadRequest(format!("Invalid multiplier {} for sport {}", multiplier, sport)));
+        }
+        if let ScoringRule::Weighted { per_km, per_minute, per_meter_elevation, per_activity } = self.rule
+            && [per_km, per_minute, per_meter_elevation, per_activity].iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return Err(ApiError::BadRequest("Weighted scoring weights must be non-negative numbers".to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn get_sport_multiplier(&self, sport_type: Option<&str>) -> f64 {
+        sport_type
+            .and_then(|sport| self.sport_multipliers.get(sport))
+            .copied()
+            .unwrap_or(1.0)
+    }
+
+    pub fn score(&self, activity: &BullSharkActivity) -> PointsBreakdown {
+        let kilometers = activity.distance.unwrap_or(0.0) / 1000.0;
+        let minutes = activity.moving_time.unwrap_or(0) as f64 / 60.0;
+        let elevation = activity.total_elevation_gain.unwrap_or(0.0);
+
+        let component = |metric: &str, value: f64, weight: f64| PointsComponent {
+            metric: metric.to_string(),
+            value,
+            weight,
+            points: value * weight,
+        };
+
+        let components = match self.rule {
+            ScoringRule::Distance => vec![component("distance_km", kilometers, 1.0)],
+            ScoringRule::MovingTime => vec![component("moving_time_minutes", minutes, 1.0)],
+            ScoringRule::ElevationGain => vec![component("elevation_gain_meters", elevation, 1.0)],
+            ScoringRule::ActivityCount => vec![component("activities", 1.0, 1.0)],
+            ScoringRule::Weighted { per_km, per_minute, per_meter_elevation, per_activity } => vec![
+                component("distance_km", kilometers, per_km),
+                component("moving_time_minutes", minutes, per_minute),
+                component("elevation_gain_meters", elevation, per_meter_elevation),
+                component("activities", 1.0, per_activity),
+            ],
+        };
+
+        let base_points: f64 = components.iter().map(|c| c.points).sum();
+        let sport_multiplier = self.get_sport_multiplier(activity.sport_type.as_deref());
+
+        PointsBreakdown {
+            activity_id: activity.id.clone(),
+            sport_type: activity.sport_type.clone(),
+            eligible: true,
+            reason: None,
+            components,
+            base_points,
+            sport_multiplier,
+            points: base_points * sport_multiplier,
+        }
+    }
+}
+
+impl PointsBreakdown {
+    pub fn ineligible(activity: &BullSharkActivity, reason: String) -> Self {
+        PointsBreakdown {
+            activity_id: activity.id.clone(),
+            sport_type: activity.sport_type.clone(),
+            eligible: false,
+            reason: Some(reason),
+            components: Vec::new(),
+            base_points: 0.0,
+            sport_multiplier: 0.0,
+            points: 0.0,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+/*
+These are external models defined by Strava.
+Returned by the authenticated athlete's /athlete/activities endpoint, and by
+/activities/{id} (which returns a superset, so the extra fields are optional).
+*/
+
+use chrono::{DateTime, Utc};
+
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+pub struct SummaryActivity {
+    pub id: i64,
+    pub resource_state: Option<i64>,
+    pub name: Option<String>,
+    pub distance: Option<f64>,
+    pub moving_time: Option<i64>,
+    pub elapsed_time: Option<i64>,
+    pub total_elevation_gain: Option<f64>,
+    pub sport_type: Option<String>,
+    pub workout_type: Option<i64>,
+    pub device_name: Option<String>,
+    pub start_date: DateTime<Utc>,
+    /// Only the id is included; /activities/{id} can return other athletes' public activities
+    pub athlete: Option<MetaAthlete>,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Debug)]
+pub struct MetaAthlete {
+    pub id: i64,
+}
@@ -0,0 +1,84 @@
+/* Internal */
+
+use std::collections::BTreeSet;
+
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::Serialize;
+
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct Streak {
+    pub length: i64,
+    /// First and last day (or week start) of the streak, None for an empty streak
+    pub start: Option<NaiveDate>,
+    pub end: Option<NaiveDate>,
+}
+
+/// Consecutive days and consecutive weeks (Monday to Sunday) with a qualifying activity
+#[derive(Serialize, Debug, Default, Clone)]
+pub struct StreakSummary {
+    /// Still alive if the last active day was today or yesterday
+    pub current_day_streak: Streak,
+    pub longest_day_streak: Streak,
+    /// Still alive if the last active week was this week or last week
+    pub current_week_streak: Streak,
+    pub longest_week_streak: Streak,
+}
+
+impl StreakSummary {
+    /// `days` are local dates with a qualifying activity, `today` in the same timezone
+    pub fn from_days(days: &BTreeSet<NaiveDate>, today: NaiveDate) -> Self {
+        let weeks: BTreeSet<NaiveDate> = days.iter().map(|day| get_week_start(*day)).collect();
+        let day_runs = find_runs(days, Duration::days(1));
+        let week_runs = find_runs(&weeks, Duration::weeks(1));
+
+        StreakSummary {
+            current_day_streak: get_current(&day_runs, today, Duration::days(1)),
+            longest_day_streak: get_longest(&day_runs),
+            current_week_streak: get_current(&week_runs, get_week_start(today), Duration::weeks(1)),
+            longest_week_streak: get_longest(&week_runs),
+        }
+    }
+}
+
+/// Monday of the week containing `day`
+pub fn get_week_start(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+// Splits sorted dates into runs where each date is `step` after the previous one
+fn find_runs(dates: &BTreeSet<NaiveDate>, step: Duration) -> Vec<Streak> {
+    let mut runs: Vec<Streak> = Vec::new();
+    for date in dates {
+        match runs.last_mut() {
+            Some(run) if run.end.is_some_and(|end| end + step == *date) => {
+                run.length += 1;
+                run.end = Some(*date);
+            }
+            _ => runs.push(Streak {
+                length: 1,
+                start: Some(*date),
+                end: Some(*date),
+            }),
+        }
+    }
+    runs
+}
+
+fn get_longest(runs: &[Streak]) -> Streak {
+    // Ties go to the most recent streak
+    runs.iter()
+        .fold(None, |longest: Option<&Streak>, run| match longest {
+            Some(longest) if longest.length > run.length => Some(longest),
+            _ => Some(run),
+        })
+        .cloned()
+        .unwrap_or_default()
+}
+
+// The last run counts as current if it reaches `period` or the one before it
+fn get_current(runs: &[Streak], period: NaiveDate, step: Duration) -> Streak {
+    match runs.last() {
+        Some(run) if run.end.is_some_and(|end| end >= period - step) => run.clone(),
+        _ => Streak::default(),
+    }
+}
@@ -0,0 +1,46 @@
+/* Internal */
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::ApiError;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Team {
+    /// Lowercase id matched against `athletes.team` and `competitions.teams`
+    pub id: String,
+    pub display_name: String,
+    /// Hex colour for the frontend, e.g. "#d62828"
+    pub color: Option<String>,
+}
+
+impl Team {
+    /// Teams referenced by a competition but missing from the teams table
+    pub fn fallback(id: &str) -> Self {
+        Team {
+            id: id.to_string(),
+            display_name: id.to_string(),
+            color: None,
+        }
+    }
+
+    pub fn validate(mut self) -> Result<Self, ApiError> {
+        self.id = Team::normalize_id(&self.id);
+        if self.id.is_empty() {
+            return Err(ApiError::BadRequest("Team id is required".to_string()));
+        }
+        if self.display_name.trim().is_empty() {
+            return Err(ApiError::BadRequest("Team display_name is required".to_string()));
+        }
+        if let Some(color) = &self.color
+            && !(color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit())) {
+            return Err(ApiError::BadRequest(format!("Team color must be a hex colour like #1d3557, got {}", color)));
+        }
+        Ok(self)
+    }
+
+    /// The id a team value refers to. Teams were seeded from `LOWER(athletes.team)`, so this is the
+    /// same normalization the queries apply with `LOWER(team)`, and every team comparison should use it.
+    pub fn normalize_id(team: &str) -> String {
+        team.trim().to_lowercase()
+    }
+}
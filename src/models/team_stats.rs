@@ -3,6 +3,8 @@ use std::collections::HashMap;
 use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
 
+use crate::models::effort::ActivityMetrics;
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct WeekData {
    #[serde(rename = "weekStart")] 
@@ -16,7 +18,7 @@ pub struct WeekData {
 }
 
 // Response structures for get_team_stats
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct TeamData {
     #[serde(rename = "athleteKilometers")]
     pub athlete_kilometers: HashMap<String, f64>,
@@ -28,4 +30,30 @@ pub struct TeamData {
 pub struct TeamStats {
     pub bulls: TeamData,
     pub sharks: TeamData,
-}
\ No newline at end of file
+}
+// Response structures for competitions with any number of teams, keyed by team id
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TeamResult {
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    pub color: Option<String>,
+    #[serde(rename = "totalKilometers")]
+    pub total_kilometers: f64,
+    /// Points under the competition's scoring rule (equal to kilometres for distance scoring)
+    #[serde(rename = "totalPoints")]
+    pub total_points: f64,
+    #[serde(rename = "athletePoints")]
+    pub athlete_points: HashMap<String, f64>,
+    /// Pace, speed and effort across the team's counted activities, keyed by sport type
+    #[serde(rename = "sportMetrics")]
+    pub sport_metrics: HashMap<String, ActivityMetrics>,
+    #[serde(flatten)]
+    pub data: TeamData,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MultiTeamStats {
+    #[serde(rename = "competitionId")]
+    pub competition_id: String,
+    pub teams: HashMap<String, TeamResult>,
+}
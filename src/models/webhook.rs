@@ -0,0 +1,41 @@
+/*
+These are external models defined by Strava's webhook events API.
+*/
+
+use serde::{Deserialize, Serialize};
+
+/// Sent as query params when Strava validates a new subscription
+#[derive(Deserialize, Debug)]
+pub struct SubscriptionChallenge {
+    #[serde(rename = "hub.mode")]
+    pub mode: String,
+    #[serde(rename = "hub.challenge")]
+    pub challenge: String,
+    #[serde(rename = "hub.verify_token")]
+    pub verify_token: String,
+}
+
+/// Echoed back to Strava to confirm the subscription
+#[derive(Serialize, Debug)]
+pub struct SubscriptionChallengeResponse {
+    #[serde(rename = "hub.challenge")]
+    pub challenge: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct WebhookEvent {
+    /// "activity" or "athlete"
+    pub object_type: String,
+    /// Activity id for activity events, athlete id for athlete events
+    pub object_id: i64,
+    /// "create", "update" or "delete"
+    pub aspect_type: String,
+    /// Strava id of the athlete who owns the object
+    pub owner_id: i64,
+    /// Checked against our subscription; events without one are rejected
+    #[serde(default)]
+    pub subscription_id: Option<i64>,
+    pub event_time: i64,
+    #[serde(default)]
+    pub updates: serde_json::Value,
+}
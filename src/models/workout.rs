@@ -0,0 +1,110 @@
+/* Internal */
+
+use chrono::{DateTime, FixedOffset, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Strava's `workout_type` codes grouped into what the athlete meant by the activity.
+/// Runs use 0-3 and rides use 10-12.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkoutCategory {
+    /// No workout type set, i.e. an easy or everyday activity
+    #[default]
+    Default,
+    Race,
+    LongRun,
+    Workout,
+}
+
+impl WorkoutCategory {
+    pub fn from_strava(workout_type: Option<i64>) -> Self {
+        match workout_type {
+            Some(1) | Some(11) => WorkoutCategory::Race,
+            Some(2) => WorkoutCategory::LongRun,
+            Some(3) | Some(12) => WorkoutCategory::Workout,
+            _ => WorkoutCategory::Default,
+        }
+    }
+
+    /// The Strava codes in this category, for filtering in SQL. Default also covers NULL.
+    pub fn get_strava_codes(&self) -> &'static [i64] {
+        match self {
+            WorkoutCategory::Default => &[0, 10],
+            WorkoutCategory::Race => &[1, 11],
+            WorkoutCategory::LongRun => &[2],
+            WorkoutCategory::Workout => &[3, 12],
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct WorkoutQuery {
+    /// RFC 3339, defaults to all time
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub team: Option<String>,
+    pub athlete_id: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RaceResult {
+    pub activity_id: String,
+    pub date: DateTime<FixedOffset>,
+    pub name: Option<String>,
+    pub athlete_id: String,
+    pub athlete_name: String,
+    /// The team the athlete was on at the time
+    pub team: String,
+    pub sport_type: Option<String>,
+    pub distance_km: f64,
+    pub moving_time_seconds: i64,
+    pub elapsed_time_seconds: i64,
+    pub pace_seconds_per_km: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct LongRunCount {
+    pub athlete_id: String,
+    pub athlete_name: String,
+    pub team: String,
+    pub long_run_count: i64,
+    pub long_run_distance_km: f64,
+    pub longest_long_run_km: f64,
+}
+
+/// Totals for one team and Strava workout type, before workout types are grouped into categories
+#[derive(Debug)]
+pub struct TeamWorkoutTypeVolume {
+    /// The team the athlete was on at the time
+    pub team: String,
+    pub workout_type: Option<i64>,
+    pub activity_count: i64,
+    /// Metres
+    pub distance: f64,
+    /// Seconds
+    pub moving_time: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct WorkoutVolume {
+    pub category: WorkoutCategory,
+    pub activity_count: i64,
+    pub distance_km: f64,
+    pub moving_time_seconds: i64,
+    /// Share of the team's distance, from 0 to 1
+    pub distance_share: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TeamWorkoutBreakdown {
+    pub team: String,
+    pub categories: Vec<WorkoutVolume>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CompetitionWorkoutBreakdown {
+    pub competition_id: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub teams: Vec<TeamWorkoutBreakdown>,
+}
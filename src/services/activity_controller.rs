@@ -1,37 +1,271 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::{BTreeMap, BTreeSet, HashMap, HashSet}, sync::Arc};
 
-use crate::{error::ApiError, models::{athlete::Athlete, bullshark::BullSharkActivity, club::ClubActivity, team_stats::{TeamData, TeamStats, WeekData}}, services::{database::Database, strava_client::StravaClient}};
-use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDateTime, Offset, TimeZone, Utc};
-use chrono_tz::America::Los_Angeles;
+use crate::{error::ApiError, models::{athlete::Athlete, competition::Competition, consistency::{AthleteConsistency, CompetitionConsistency, TeamParticipation, TeamParticipationWeek}, effort::{ActivityMetrics, SportEffortTotals}, export::TeamStatsExportRow, scoring::PointsBreakdown, streak::{self, StreakSummary}, bullshark::{ActivityDeletion, BullSharkActivity, DateConfidence, DeletionReason, RosterActivity}, club::ClubActivity, rate_limit::RateLimitStatus, live::{LiveTeamTotals, LiveUpdate, LiveUpdateKind}, team::Team, team_stats::{MultiTeamStats, TeamData, TeamResult, TeamStats, WeekData}, workout::WorkoutCategory}, services::{athlete_resolver::{self, AthleteResolver}, database::Database, date_resolver::DateResolver, strava_client::StravaClient}, utils::database_utils};
+use chrono::{DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc};
+use chrono_tz::Tz;
 use sha2::{Digest, Sha256};
+use tokio::sync::broadcast;
+
+const CLUB_ACTIVITIES_PER_PAGE: u32 = 100;
+// Updates a slow subscriber can fall behind by before it's told to resync
+const LIVE_UPDATE_CAPACITY: usize = 16;
+
+// Per-team aggregates before they're shaped into a response
+struct TeamTotals {
+    data: TeamData,
+    athlete_points: HashMap<String, f64>,
+    effort: SportEffortTotals,
+}
+
+// Everything we learned from paging the club feed during one sync
+struct ClubSync {
+    new_activities: Vec<ClubActivity>,
+    // Hash ids of every activity we read, new or stored, in feed order
+    seen_ids: Vec<String>,
+    // The oldest already-stored activity we read, if we got that far
+    anchor_id: Option<String>,
+}
 
 pub struct ActivityController {
     db: Arc<Database>,
     strava_client: StravaClient, 
+    date_resolver: DateResolver,
+    live_updates: broadcast::Sender<Arc<LiveUpdate>>,
 }
 
 impl ActivityController {
-    pub fn new(db: Arc<Database>, strava_client: StravaClient) -> Self {
+    pub fn new(db: Arc<Database>, strava_client: StravaClient, date_resolver: DateResolver) -> Self {
+        let (live_updates, _) = broadcast::channel(LIVE_UPDATE_CAPACITY);
         ActivityController { 
             db,
-            strava_client
+            strava_client,
+            date_resolver,
+            live_updates,
         }
     }
 
+    /// Receives an update each time activities are stored, edited or deleted
+    pub fn subscribe_live_updates(&self) -> broadcast::Receiver<Arc<LiveUpdate>> {
+        self.live_updates.subscribe()
+    }
+
     pub async fn populate_new_activities(&self) -> Result<(), ApiError> {
         println!("Populating new activities...");
-        let new_activities = self.strava_client.read_last_100_activities().await?;
-        println!("Found {} new activities...", new_activities.len());
-        let new_bullshark_activities = self.convert_activities(&new_activities)?;
+        // Anything uploaded after this lands in the next sync's window
+        let sync_started_at = Utc::now();
+        let sync = self.sync_club_activities().await?;
+        println!("Found {} new activities...", sync.new_activities.len());
+        let first_seen_at = Utc::now();
+        let mut new_bullshark_activities = self.convert_activities(&sync.new_activities, first_seen_at)?;
+        let resolver = AthleteResolver::load(&self.db).await?;
+        let mut unmatched = 0;
+        for activity in new_bullshark_activities.iter_mut() {
+            activity.athlete_id = activity.athlete_name
+                .as_deref()
+                .and_then(|name| resolver.resolve(name))
+                .map(|athlete| athlete.id.clone());
+            if activity.athlete_id.is_none() {
+                unmatched += 1;
+            }
+        }
+        if unmatched > 0 {
+            println!("{} new activities are from athletes not on the roster, see /athletes/unmatched", unmatched);
+        }
+        println!("Resolving activity dates...");
+        self.date_resolver.resolve_dates(&self.strava_client, &resolver, &mut new_bullshark_activities, first_seen_at).await?;
+        println!("Reconciling edited and removed activities...");
+        let deletions = self.reconcile_sync_window(&sync, &mut new_bullshark_activities).await?;
         println!("Inserting bullshark activities to the database...");
         self.db.insert_activities(&new_bullshark_activities).await?;
+        self.db.soft_delete_activities(&deletions).await?;
+        self.db.insert_sync_run(sync_started_at, new_bullshark_activities.len()).await?;
+        self.publish_live_updates(Self::get_sync_live_updates(new_bullshark_activities, deletions)).await;
         println!("Populate new activities complete.");
         Ok(())
     }
 
-    pub fn convert_activities(&self, club_activities: &[ClubActivity]) -> Result<Vec<BullSharkActivity>, ApiError> {
-        // Get current UTC time and convert to FixedOffset for model compatibility
-        let batch_time = Utc::now().with_timezone(&FixedOffset::east_opt(0).unwrap());
+    /// Splits a sync into new activities, edits that replaced a stored activity, and removals
+    fn get_sync_live_updates(activities: Vec<BullSharkActivity>, deletions: Vec<ActivityDeletion>) -> Vec<LiveUpdate> {
+        let mut edited: HashMap<String, String> = HashMap::new();
+        let mut removed_ids: Vec<String> = Vec::new();
+        for deletion in deletions {
+            match deletion.superseded_by {
+                Some(new_id) => {
+                    edited.insert(new_id, deletion.id);
+                }
+                None => removed_ids.push(deletion.id),
+            }
+        }
+
+        let (updated, created): (Vec<BullSharkActivity>, Vec<BullSharkActivity>) = activities
+            .into_iter()
+            .partition(|activity| edited.contains_key(&activity.id));
+        let replaced_ids = updated.iter().filter_map(|activity| edited.remove(&activity.id)).collect();
+
+        vec![
+            LiveUpdate::new(LiveUpdateKind::Created, created, Vec::new()),
+            LiveUpdate::new(LiveUpdateKind::Updated, updated, replaced_ids),
+            LiveUpdate::new(LiveUpdateKind::Deleted, Vec::new(), removed_ids),
+        ]
+    }
+
+    /*
+    Team totals are computed once per change here rather than per subscriber, however many are listening.
+    A failure only costs subscribers the totals; the change itself was already stored.
+    */
+    async fn publish_live_updates(&self, mut updates: Vec<LiveUpdate>) {
+        updates.retain(|update| !update.is_empty());
+        if updates.is_empty() || self.live_updates.receiver_count() == 0 {
+            return;
+        }
+
+        let team_totals = match self.get_current_team_stats().await {
+            Ok(stats) => Some(LiveTeamTotals::from(stats)),
+            Err(ApiError::NotFound(_)) => None,
+            Err(e) => {
+                eprintln!("[ACTIVITY_CONTROLLER] publish_live_updates: failed to compute team totals: {:?}", e);
+                None
+            }
+        };
+
+        for mut update in updates {
+            update.team_totals = team_totals.clone();
+            let kind = update.kind;
+            let change_count = update.activities.len().max(update.removed_ids.len());
+            // Only fails when every subscriber disconnected since the check above
+            if let Ok(subscribers) = self.live_updates.send(Arc::new(update)) {
+                println!("[ACTIVITY_CONTROLLER] publish_live_updates: sent {} {} to {} subscribers", change_count, kind.get_event_name(), subscribers);
+            }
+        }
+    }
+
+    // Strava returns club activities newest first, so we keep paging until a page contains
+    // an activity we've already stored, the club runs out of activities, or we hit the page cap.
+    // A page that ends on a stored activity may have more of the anchor's batch on the next page,
+    // so paging continues until the anchor is followed by an older activity.
+    async fn sync_club_activities(&self) -> Result<ClubSync, ApiError> {
+        let max_pages = self.strava_client.get_sync_max_pages();
+        let mut club_activities: Vec<ClubActivity> = Vec::new();
+        let mut seen_ids: Vec<String> = Vec::new();
+        let mut anchor_id: Option<String> = None;
+
+        for page in 1..=max_pages {
+            let page_activities = self.strava_client.read_club_activities_page(page, CLUB_ACTIVITIES_PER_PAGE).await?;
+            let page_len = page_activities.len();
+            println!("[ACTIVITY_CONTROLLER] sync_club_activities: page {} returned {} activities", page, page_len);
+
+            let page_ids = page_activities
+                .iter()
+                .map(|activity| self.create_hash_for_activity(activity))
+                .collect::<Result<Vec<String>, ApiError>>()?;
+            let existing_ids = self.db.get_existing_activity_ids(&page_ids).await?;
+
+            let reached_stored = !existing_ids.is_empty();
+            if let Some(last_stored) = page_ids.iter().rev().find(|id| existing_ids.contains(*id)) {
+                anchor_id = Some(last_stored.clone());
+            }
+            seen_ids.extend(page_ids.iter().cloned());
+            club_activities.extend(
+                page_activities
+                    .into_iter()
+                    .zip(page_ids)
+                    .filter(|(_, id)| !existing_ids.contains(id))
+                    .map(|(activity, _)| activity)
+            );
+
+            if page_len < CLUB_ACTIVITIES_PER_PAGE as usize {
+                break;
+            }
+            if reached_stored && anchor_id.as_ref() != seen_ids.last() {
+                println!("[ACTIVITY_CONTROLLER] sync_club_activities: reached stored activities on page {}", page);
+                break;
+            }
+            if page == max_pages {
+                println!("[ACTIVITY_CONTROLLER] sync_club_activities: hit page cap of {} before reaching stored activities", max_pages);
+            }
+        }
+
+        Ok(ClubSync {
+            new_activities: club_activities,
+            seen_ids,
+            anchor_id,
+        })
+    }
+
+    /*
+    The club feed has no activity ids, so an edit (e.g. cropping a run) shows up as a new hash
+    and the old version silently drops out of the feed. Every stored activity newer than the
+    oldest stored activity we saw this sync should still be in the feed; any that aren't were
+    either edited (a new activity from the same athlete with the same name and sport replaces it)
+    or removed. Edited versions inherit the original's dates.
+    */
+    async fn reconcile_sync_window(&self, sync: &ClubSync, new_activities: &mut [BullSharkActivity]) -> Result<Vec<ActivityDeletion>, ApiError> {
+        let restored = self.db.restore_removed_activities(&sync.seen_ids).await?;
+        if restored > 0 {
+            println!("[ACTIVITY_CONTROLLER] reconcile_sync_window: restored {} activities that reappeared", restored);
+        }
+
+        let anchor_id = match &sync.anchor_id {
+            Some(anchor_id) => anchor_id,
+            None => {
+                println!("[ACTIVITY_CONTROLLER] reconcile_sync_window: no stored activity in this sync window, skipping");
+                return Ok(Vec::new());
+            }
+        };
+
+        let unseen = self.db.get_unseen_activities_since(anchor_id, &sync.seen_ids).await?;
+        let mut claimed: Vec<bool> = vec![false; new_activities.len()];
+        let mut deletions: Vec<ActivityDeletion> = Vec::new();
+
+        for old in unseen {
+            let replacement = new_activities
+                .iter()
+                .enumerate()
+                .filter(|(index, new)| {
+                    !claimed[*index]
+                        && new.athlete_name == old.athlete_name
+                        && new.name == old.name
+                        && new.sport_type == old.sport_type
+                })
+                .min_by(|(_, a), (_, b)| {
+                    let a_diff = (a.distance.unwrap_or(0.0) - old.distance.unwrap_or(0.0)).abs();
+                    let b_diff = (b.distance.unwrap_or(0.0) - old.distance.unwrap_or(0.0)).abs();
+                    a_diff.total_cmp(&b_diff)
+                })
+                .map(|(index, _)| index);
+
+            match replacement {
+                Some(index) => {
+                    claimed[index] = true;
+                    let new = &mut new_activities[index];
+                    new.date = old.date;
+                    new.first_seen_at = old.first_seen_at;
+                    new.date_confidence = old.date_confidence;
+                    println!("[ACTIVITY_CONTROLLER] reconcile_sync_window: activity {} was edited, superseded by {}", old.id, new.id);
+                    deletions.push(ActivityDeletion {
+                        id: old.id,
+                        reason: DeletionReason::Edited,
+                        superseded_by: Some(new.id.clone()),
+                    });
+                }
+                None => {
+                    println!("[ACTIVITY_CONTROLLER] reconcile_sync_window: activity {} was removed from the club feed", old.id);
+                    deletions.push(ActivityDeletion {
+                        id: old.id,
+                        reason: DeletionReason::Removed,
+                        superseded_by: None,
+                    });
+                }
+            }
+        }
+
+        Ok(deletions)
+    }
+
+    pub fn convert_activities(&self, club_activities: &[ClubActivity], first_seen_at: DateTime<Utc>) -> Result<Vec<BullSharkActivity>, ApiError> {
+        // Convert to FixedOffset for model compatibility. Dates start as the first seen time
+        // and are refined by the DateResolver before insert.
+        let batch_time = first_seen_at.with_timezone(&FixedOffset::east_opt(0).unwrap());
 
         club_activities
             .iter()
@@ -52,8 +286,12 @@ impl ActivityController {
 
         Ok(BullSharkActivity {
             id: hash,
+            strava_activity_id: None,
             date: time, 
+            first_seen_at: time,
+            date_confidence: DateConfidence::FirstSeen,
             athlete_name: Some(athlete_name),
+            athlete_id: None,
             resource_state: club_activity.resource_state,
             name: club_activity.name.clone(),
             distance: club_activity.distance,
@@ -62,7 +300,9 @@ impl ActivityController {
             total_elevation_gain: club_activity.total_elevation_gain,
             sport_type: club_activity.sport_type.clone(),
             workout_type: club_activity.workout_type,
-            device_name: club_activity.device_name.clone()
+            workout_category: WorkoutCategory::from_strava(club_activity.workout_type),
+            device_name: club_activity.device_name.clone(),
+            metrics: ActivityMetrics::new(club_activity.distance, club_activity.moving_time, club_activity.elapsed_time, club_activity.total_elevation_gain),
         })
     }
 
@@ -88,18 +328,100 @@ impl ActivityController {
       let elapsed_time = club_activity.elapsed_time
           .ok_or_else(|| ApiError::InternalConversionError("Activity missing elapsed time".to_string()))?;
 
-      let composite = format!(
-          "{}|{}|{}|{}|{}",
-          first_name,
-          last_name,
-          distance,
-          moving_time,
-          elapsed_time
-      );
+        Ok(self.hash_activity_fields(first_name, last_name, distance, moving_time, elapsed_time))
+    }
+
+    // Shared by the club feed and webhooks so the same activity always gets the same id
+    fn hash_activity_fields(&self, first_name: &str, last_name: &str, distance: f64, moving_time: i64, elapsed_time: i64) -> String {
+        let composite = format!(
+            "{}|{}|{}|{}|{}",
+            first_name,
+            last_name,
+            distance,
+            moving_time,
+            elapsed_time
+        );
 
         let mut hasher = Sha256::new();
         hasher.update(composite.as_bytes());
-        Ok(format!("{:x}", hasher.finalize()))
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Fetch a single activity with the owning athlete's token and upsert it.
+    /// Used by webhooks, where we get the real Strava id and start date.
+    pub async fn upsert_strava_activity(&self, owner_id: i64, activity_id: i64) -> Result<(), ApiError> {
+        let strava_id = owner_id.to_string();
+        let athlete = match self.db.read_athlete_by_strava_id(&strava_id).await? {
+            Some(athlete) => athlete,
+            None => {
+                println!("[ACTIVITY_CONTROLLER] upsert_strava_activity: Strava athlete {} is not linked to a roster athlete, skipping", owner_id);
+                return Ok(());
+            }
+        };
+
+        let strava_activity = self.strava_client.read_activity(&strava_id, activity_id).await?;
+        if strava_activity.athlete.as_ref().is_none_or(|athlete| athlete.id != owner_id) {
+            println!("[ACTIVITY_CONTROLLER] upsert_strava_activity: Activity {} isn't owned by Strava athlete {}, skipping", activity_id, owner_id);
+            return Ok(());
+        }
+        let distance = strava_activity.distance
+            .ok_or_else(|| ApiError::InternalConversionError("Activity missing distance".to_string()))?;
+        let moving_time = strava_activity.moving_time
+            .ok_or_else(|| ApiError::InternalConversionError("Activity missing moving time".to_string()))?;
+        let elapsed_time = strava_activity.elapsed_time
+            .ok_or_else(|| ApiError::InternalConversionError("Activity missing elapsed time".to_string()))?;
+
+        // Hash the "First L." form of the roster name so the club feed's copy gets the same id.
+        // Members can name themselves differently on Strava, so an unclaimed club row with the same stats is taken over too.
+        let (first_name, last_name) = athlete_resolver::get_club_name_parts(&athlete.name)
+            .unwrap_or_else(|| (athlete.name.clone(), String::new()));
+        let hash = match self.db.read_matching_club_activity_id(&athlete.id, distance, moving_time, elapsed_time).await? {
+            Some(club_activity_id) => club_activity_id,
+            None => self.hash_activity_fields(&first_name, &last_name, distance, moving_time, elapsed_time),
+        };
+
+        let activity = BullSharkActivity {
+            id: hash,
+            strava_activity_id: Some(strava_activity.id),
+            date: database_utils::to_pacific(strava_activity.start_date),
+            first_seen_at: database_utils::to_pacific(Utc::now()),
+            date_confidence: DateConfidence::Exact,
+            athlete_name: Some(format!("{} {}", first_name, last_name).trim_end().to_string()),
+            athlete_id: Some(athlete.id.clone()),
+            resource_state: strava_activity.resource_state,
+            name: strava_activity.name,
+            distance: Some(distance),
+            moving_time: Some(moving_time),
+            elapsed_time: Some(elapsed_time),
+            total_elevation_gain: strava_activity.total_elevation_gain,
+            sport_type: strava_activity.sport_type,
+            workout_type: strava_activity.workout_type,
+            workout_category: WorkoutCategory::from_strava(strava_activity.workout_type),
+            device_name: strava_activity.device_name,
+            metrics: ActivityMetrics::new(Some(distance), Some(moving_time), Some(elapsed_time), strava_activity.total_elevation_gain),
+        };
+
+        let upsert = self.db.upsert_activity(&activity).await?;
+        let kind = if upsert.existed || !upsert.replaced_ids.is_empty() {
+            LiveUpdateKind::Updated
+        } else {
+            LiveUpdateKind::Created
+        };
+        self.publish_live_updates(vec![LiveUpdate::new(kind, vec![activity], upsert.replaced_ids)]).await;
+        Ok(())
+    }
+
+    /// Only removes the activity from the roster athlete linked to `owner_id`
+    pub async fn delete_strava_activity(&self, owner_id: i64, activity_id: i64) -> Result<(), ApiError> {
+        let Some(athlete) = self.db.read_athlete_by_strava_id(&owner_id.to_string()).await? else {
+            println!("[ACTIVITY_CONTROLLER] delete_strava_activity: Strava athlete {} is not linked to a roster athlete, skipping", owner_id);
+            return Ok(());
+        };
+
+        let deleted_ids = self.db.soft_delete_activity_by_strava_id(activity_id, &athlete.id).await?;
+        println!("[ACTIVITY_CONTROLLER] delete_strava_activity: removed {} rows for Strava activity {}", deleted_ids.len(), activity_id);
+        self.publish_live_updates(vec![LiveUpdate::new(LiveUpdateKind::Deleted, Vec::new(), deleted_ids)]).await;
+        Ok(())
     }
 
     pub async fn health_check_strava(&self) -> Result<(), ApiError> {
@@ -107,37 +429,101 @@ impl ActivityController {
         self.strava_client.health_check().await
     }
 
+    pub fn get_strava_rate_limit(&self) -> Option<RateLimitStatus> {
+        self.strava_client.get_rate_limit_status()
+    }
+
+    /// Team stats in the original bulls/sharks shape, for the current competition
     pub async fn get_team_stats(&self) -> Result<TeamStats, ApiError> {
-        let athlete_teams = self.build_athlete_team_map().await?;
-        let (start_date, end_date) = self.get_team_stat_dates()?;
+        let competition = self.get_current_competition().await?;
+        let mut teams = self.build_team_data(&competition).await?;
 
-        println!("[ACTIVITY_CONTROLLER]: getting team stats from {} to {}", start_date, end_date);
+        Ok(TeamStats {
+            bulls: teams.remove("bulls").map(|totals| totals.data).unwrap_or_default(),
+            sharks: teams.remove("sharks").map(|totals| totals.data).unwrap_or_default(),
+        })
+    }
 
-        let activities = self.db.get_activities_from_window(start_date, end_date).await?;
+    pub async fn get_current_team_stats(&self) -> Result<MultiTeamStats, ApiError> {
+        let competition = self.get_current_competition().await?;
+        self.get_multi_team_stats(&competition).await
+    }
 
-        let mut bulls_athlete_kilometers: HashMap<String, f64> = HashMap::new();
-        let mut bulls_week_data: HashMap<NaiveDateTime, WeekData> = HashMap::new();
-        let mut sharks_athlete_kilometers: HashMap<String, f64> = HashMap::new();
-        let mut sharks_week_data: HashMap<NaiveDateTime, WeekData> = HashMap::new();
+    pub async fn get_competition_team_stats(&self, competition_id: &str) -> Result<MultiTeamStats, ApiError> {
+        let competition = self.db.read_competition(competition_id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No competition found with id: {}", competition_id)))?;
+        self.get_multi_team_stats(&competition).await
+    }
 
-        // O(n) over each activity
-        for activity in activities {
-            if !self.valid_activity(&activity) {
-                continue;
-            }
+    pub async fn get_current_consistency(&self) -> Result<CompetitionConsistency, ApiError> {
+        let competition = self.get_current_competition().await?;
+        self.get_consistency(&competition).await
+    }
 
-            // Get athlete name
-            let athlete_name = match &activity.athlete_name {
-                Some(name) => name,
-                None => continue,
-            };
+    pub async fn get_competition_consistency(&self, competition_id: &str) -> Result<CompetitionConsistency, ApiError> {
+        let competition = self.db.read_competition(competition_id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No competition found with id: {}", competition_id)))?;
+        self.get_consistency(&competition).await
+    }
 
-            // Get athlete team
-            let team = match athlete_teams.get(athlete_name) {
-                Some(t) => t,
-                None => continue,
-            };
+    async fn get_current_competition(&self) -> Result<Competition, ApiError> {
+        self.db.read_current_competition().await?
+            .ok_or_else(|| ApiError::NotFound("No competition has started yet".to_string()))
+    }
+
+    async fn get_multi_team_stats(&self, competition: &Competition) -> Result<MultiTeamStats, ApiError> {
+        let team_data = self.build_team_data(competition).await?;
+        let mut team_details: HashMap<String, Team> = self.db.read_all_teams().await?
+            .into_iter()
+            .map(|team| (team.id.clone(), team))
+            .collect();
+
+        let teams = team_data
+            .into_iter()
+            .map(|(team_id, totals)| {
+                let team = team_details.remove(&team_id).unwrap_or_else(|| Team::fallback(&team_id));
+                let total_kilometers = totals.data.athlete_kilometers.values().sum();
+                let total_points = totals.athlete_points.values().sum();
+                (team_id, TeamResult {
+                    display_name: team.display_name,
+                    color: team.color,
+                    total_kilometers,
+                    total_points,
+                    athlete_points: totals.athlete_points,
+                    sport_metrics: totals.effort.get_metrics(),
+                    data: totals.data,
+                })
+            })
+            .collect();
 
+        Ok(MultiTeamStats {
+            competition_id: competition.id.clone(),
+            teams,
+        })
+    }
+
+    /// Per-team athlete and weekly kilometres for every team in the competition
+    async fn build_team_data(&self, competition: &Competition) -> Result<HashMap<String, TeamTotals>, ApiError> {
+        let (start_date, end_date) = competition.get_window();
+        let timezone = competition.get_timezone()?;
+
+        println!("[ACTIVITY_CONTROLLER]: getting team stats for competition '{}' from {} to {}", competition.id, start_date, end_date);
+
+        let activities = self.get_counted_activities(competition, start_date, end_date).await?;
+
+        let mut team_athlete_kilometers: HashMap<String, HashMap<String, f64>> = HashMap::new();
+        let mut team_athlete_points: HashMap<String, HashMap<String, f64>> = HashMap::new();
+        let mut team_effort: HashMap<String, SportEffortTotals> = HashMap::new();
+        let mut team_week_data: HashMap<String, HashMap<NaiveDateTime, WeekData>> = HashMap::new();
+        for team in &competition.teams {
+            team_athlete_kilometers.insert(team.clone(), HashMap::new());
+            team_athlete_points.insert(team.clone(), HashMap::new());
+            team_effort.insert(team.clone(), SportEffortTotals::default());
+            team_week_data.insert(team.clone(), HashMap::new());
+        }
+
+        // O(n) over each activity
+        for RosterActivity { activity, athlete_name, team } in activities {
             // Get activity distance (kilometers)
             let distance_meters = match activity.distance {
                 Some(d) => d,
@@ -145,30 +531,38 @@ impl ActivityController {
             };
             let distance_kilometers = distance_meters / 1000.0;
 
-            // find the right hashmap for this athlete
-            let athlete_kilometers = match team.as_str() {
-                "bulls" => &mut bulls_athlete_kilometers,
-                "sharks" => &mut sharks_athlete_kilometers,
-                _ => continue,
+            // find the right hashmap for this athlete, skipping teams outside the competition
+            let athlete_kilometers = match team_athlete_kilometers.get_mut(&team) {
+                Some(athlete_kilometers) => athlete_kilometers,
+                None => continue,
             };
             // update athlete hashmap
             *athlete_kilometers.entry(athlete_name.clone()).or_insert(0.0) += distance_kilometers;
 
-            let start_of_week = self.get_start_of_week_for_activity(&activity);
+            // Points follow the competition's scoring rule, kilometres are always raw distance
+            if let Some(athlete_points) = team_athlete_points.get_mut(&team) {
+                let points = competition.scoring.score(&activity).points;
+                *athlete_points.entry(athlete_name.clone()).or_insert(0.0) += points;
+            }
+
+            if let Some(effort) = team_effort.get_mut(&team) {
+                effort.add(&activity);
+            }
+
+            let start_of_week = self.get_start_of_week_for_activity(&activity, &timezone);
 
             // Update weekly kilometers for that week
-            let weekly_kilometers = match team.as_str() {
-                "bulls" => &mut bulls_week_data,
-                "sharks" => &mut sharks_week_data,
-                _ => continue,
+            let weekly_kilometers = match team_week_data.get_mut(&team) {
+                Some(weekly_kilometers) => weekly_kilometers,
+                None => continue,
             };
 
-            let pacific_dt = Los_Angeles.from_local_datetime(&start_of_week).single()
+            let local_dt = timezone.from_local_datetime(&start_of_week).earliest()
                 .ok_or_else(|| ApiError::InternalConversionError(format!("Invalid datetime conversion for week start: {}", start_of_week)))?;
-            let week_start = pacific_dt.with_timezone(&pacific_dt.offset().fix());
+            let week_start = local_dt.with_timezone(&local_dt.offset().fix());
 
             let week_data = weekly_kilometers.entry(start_of_week).or_insert(WeekData { 
-                week_start: week_start, 
+                week_start, 
                 weekly_team_kilometers: 0.0, 
                 weekly_running_sum: 0.0, 
                 weekly_athlete_kilometers: HashMap::new() 
@@ -179,69 +573,270 @@ impl ActivityController {
         }
 
         // Convert to vec, compute running sums, sort entries, etc. 
-        let bulls_weekly_vec = self.convert_weekly_map_to_vec(bulls_week_data)?;
-        let sharks_weekly_vec = self.convert_weekly_map_to_vec(sharks_week_data)?;
-
-        let team_stats = TeamStats {
-            bulls: TeamData {
-                athlete_kilometers: bulls_athlete_kilometers,
-                weekly_kilometers: bulls_weekly_vec,
-            },
-            sharks: TeamData {
-                athlete_kilometers: sharks_athlete_kilometers,
-                weekly_kilometers: sharks_weekly_vec,
-            },
-        };
+        let mut teams: HashMap<String, TeamTotals> = HashMap::new();
+        for (team, week_data) in team_week_data {
+            let weekly_vec = self.convert_weekly_map_to_vec(week_data)?;
+            teams.insert(team.clone(), TeamTotals {
+                data: TeamData {
+                    athlete_kilometers: team_athlete_kilometers.remove(&team).unwrap_or_default(),
+                    weekly_kilometers: weekly_vec,
+                },
+                athlete_points: team_athlete_points.remove(&team).unwrap_or_default(),
+                effort: team_effort.remove(&team).unwrap_or_default(),
+            });
+        }
 
         println!("[API] get_team_stats: Successfully calculated team stats");
-        Ok(team_stats)
+        Ok(teams)
     }
 
-    pub fn valid_activity(&self, activity: &BullSharkActivity) -> bool {
-        if let Some(sport_type) = &activity.sport_type {
-            if sport_type != "Run" {
-                return false;
+    /// The activities team stats count between `start` and `end`: competition sports with a distance,
+    /// from athletes on one of the competition's teams at the time. Teams are normalized to ids.
+    async fn get_counted_activities(&self, competition: &Competition, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<RosterActivity>, ApiError> {
+        let activities = self.db.get_roster_activities_from_window(start, end).await?;
+        Ok(activities
+            .into_iter()
+            .filter(|roster_activity| self.valid_activity(&roster_activity.activity, competition) && roster_activity.activity.distance.is_some())
+            .map(|roster_activity| RosterActivity {
+                team: Team::normalize_id(&roster_activity.team),
+                ..roster_activity
+            })
+            .filter(|roster_activity| competition.teams.contains(&roster_activity.team))
+            .collect())
+    }
+
+    /// Team stats broken down by team, week and athlete, counting the same activities as team stats.
+    /// `start` and `end` replace the competition's window, and `sport_type` and `team` narrow it further.
+    pub async fn get_team_week_rows(&self, competition: &Competition, start: DateTime<Utc>, end: DateTime<Utc>, sport_type: Option<&str>, team: Option<&str>) -> Result<Vec<TeamStatsExportRow>, ApiError> {
+        let timezone = competition.get_timezone()?;
+        let activities = self.get_counted_activities(competition, start, end).await?;
+
+        // Ordered like the export: by team, week, then athlete
+        let mut rows: BTreeMap<(String, NaiveDate, String, String), TeamStatsExportRow> = BTreeMap::new();
+        for RosterActivity { activity, athlete_name, team: activity_team } in activities {
+            if sport_type.is_some_and(|sport_type| activity.sport_type.as_deref() != Some(sport_type))
+                || team.is_some_and(|team| activity_team != team) {
+                continue;
             }
-        } else {
-            return false; 
+            let Some(athlete_id) = activity.athlete_id.clone() else {
+                continue;
+            };
+            let week_start = self.get_start_of_week_for_activity(&activity, &timezone).date();
+
+            let row = rows
+                .entry((activity_team.clone(), week_start, athlete_name.clone(), athlete_id.clone()))
+                .or_insert_with(|| TeamStatsExportRow {
+                    team: activity_team,
+                    week_start,
+                    athlete_id,
+                    athlete_name,
+                    activity_count: 0,
+                    distance_km: 0.0,
+                    moving_time_seconds: 0,
+                    elevation_gain_meters: 0.0,
+                });
+            row.activity_count += 1;
+            row.distance_km += activity.distance.unwrap_or(0.0) / 1000.0;
+            row.moving_time_seconds += activity.moving_time.unwrap_or(0);
+            row.elevation_gain_meters += activity.total_elevation_gain.unwrap_or(0.0);
         }
-        return true;
+
+        println!("[ACTIVITY_CONTROLLER] get_team_week_rows: {} rows for competition '{}'", rows.len(), competition.id);
+        Ok(rows.into_values().collect())
+    }
+
+    /// Re-resolves every athlete name on stored activities and saves the athlete id.
+    /// Names that no longer resolve, e.g. after a rename or a new ambiguity, have their id cleared.
+    /// Webhook activities keep the id of the athlete who owns them.
+    /// Run after binding aliases or changing the roster so older activities are attributed.
+    pub async fn backfill_athlete_ids(&self) -> Result<u64, ApiError> {
+        let resolver = AthleteResolver::load(&self.db).await?;
+        let (athlete_names, athlete_ids): (Vec<String>, Vec<Option<String>>) = self.db.read_activity_athlete_names().await?
+            .into_iter()
+            .map(|(name, _, _)| {
+                let athlete_id = resolver.resolve(&name).map(|athlete| athlete.id.clone());
+                (name, athlete_id)
+            })
+            .unzip();
+
+        let resolved = athlete_ids.iter().filter(|athlete_id| athlete_id.is_some()).count();
+        let updated = self.db.set_activity_athlete_ids(&athlete_names, &athlete_ids).await?;
+        println!("[ACTIVITY_CONTROLLER] backfill_athlete_ids: Resolved {} of {} names, updated {} activities", resolved, athlete_names.len(), updated);
+        Ok(updated)
     }
 
-    pub async fn build_athlete_team_map(&self) -> Result<HashMap<String, String>, ApiError> {
-        let athletes = self.db.read_all_athletes().await?;
-        let mut athlete_teams: HashMap<String, String> = HashMap::new();
-        for athlete in athletes {
-            athlete_teams.insert(athlete.name.clone(), athlete.team.clone());
+    /// Runs the backfill without holding up the roster change that triggered it
+    pub fn spawn_backfill_athlete_ids(self: &Arc<Self>) {
+        let controller = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Err(e) = controller.backfill_athlete_ids().await {
+                eprintln!("[ACTIVITY_CONTROLLER] spawn_backfill_athlete_ids: backfill failed: {:?}", e);
+            }
+        });
+    }
+
+    /// Streaks and weekly consistency per athlete, and weekly participation per team.
+    /// Days and weeks are in the competition's timezone and any activity of a competition sport qualifies.
+    async fn get_consistency(&self, competition: &Competition) -> Result<CompetitionConsistency, ApiError> {
+        let (start_date, end_date) = competition.get_window();
+        let timezone = competition.get_timezone()?;
+        let first_week = streak::get_week_start(start_date.with_timezone(&timezone).date_naive());
+        // Weeks that haven't started yet don't count against anyone
+        let last_day = end_date.min(Utc::now()).with_timezone(&timezone).date_naive();
+
+        let mut weeks: Vec<NaiveDate> = Vec::new();
+        let mut week = first_week;
+        while week <= last_day {
+            weeks.push(week);
+            week += Duration::weeks(1);
+        }
+
+        println!("[ACTIVITY_CONTROLLER]: getting consistency for competition '{}' over {} weeks", competition.id, weeks.len());
+        let activities = self.db.get_roster_activities_from_window(start_date, end_date).await?;
+        let memberships = self.db.read_all_team_memberships().await?;
+
+        let mut athlete_days: HashMap<String, BTreeSet<NaiveDate>> = HashMap::new();
+        let mut team_week_athletes: HashMap<(String, NaiveDate), HashSet<String>> = HashMap::new();
+        for RosterActivity { activity, team, .. } in activities {
+            if !self.valid_activity(&activity, competition) {
+                continue;
+            }
+            let Some(athlete_id) = activity.athlete_id else {
+                continue;
+            };
+            let day = activity.date.with_timezone(&timezone).date_naive();
+            athlete_days.entry(athlete_id.clone()).or_default().insert(day);
+            team_week_athletes
+                .entry((Team::normalize_id(&team), streak::get_week_start(day)))
+                .or_default()
+                .insert(athlete_id);
+        }
+
+        // Everyone currently on a competing team is expected to show up, active or not
+        let current_teams: HashMap<&String, String> = memberships
+            .iter()
+            .filter(|(_, membership)| membership.valid_to.is_none())
+            .map(|(athlete_id, membership)| (athlete_id, Team::normalize_id(&membership.team)))
+            .collect();
+        let active_ids: HashSet<String> = athlete_days.keys().cloned().collect();
+        let mut athletes: Vec<AthleteConsistency> = self.db.read_all_athletes().await?
+            .into_iter()
+            .map(|athlete| {
+                let team = current_teams.get(&athlete.id).cloned().unwrap_or_else(|| Team::normalize_id(&athlete.team));
+                (athlete, team)
+            })
+            .filter(|(athlete, team)| competition.teams.contains(team) || active_ids.contains(&athlete.id))
+            .map(|(athlete, team)| {
+                let days = athlete_days.remove(&athlete.id).unwrap_or_default();
+                let active_weeks = days.iter().map(|day| streak::get_week_start(*day)).collect::<HashSet<NaiveDate>>().len();
+                AthleteConsistency {
+                    active_weeks,
+                    total_weeks: weeks.len(),
+                    consistency: if weeks.is_empty() { 0.0 } else { active_weeks as f64 / weeks.len() as f64 },
+                    streaks: StreakSummary::from_days(&days, last_day),
+                    athlete_id: athlete.id,
+                    athlete_name: athlete.name,
+                    team,
+                }
+            })
+            .collect();
+        athletes.sort_by(|a, b| b.consistency.total_cmp(&a.consistency).then_with(|| a.athlete_name.cmp(&b.athlete_name)));
+
+        let mut teams: Vec<TeamParticipation> = Vec::new();
+        for team in &competition.teams {
+            let mut team_weeks: Vec<TeamParticipationWeek> = Vec::with_capacity(weeks.len());
+            for week in &weeks {
+                let week_start = timezone.from_local_datetime(&week.and_hms_opt(0, 0, 0).unwrap()).earliest()
+                    .ok_or_else(|| ApiError::InternalConversionError(format!("Invalid datetime conversion for week start: {}", week)))?
+                    .with_timezone(&Utc);
+                let week_end = week_start + Duration::weeks(1);
+
+                let roster_size = memberships
+                    .iter()
+                    .filter(|(_, membership)| {
+                        Team::normalize_id(&membership.team) == *team
+                            && membership.valid_from < week_end
+                            && membership.valid_to.is_none_or(|valid_to| valid_to > week_start)
+                    })
+                    .map(|(athlete_id, _)| athlete_id)
+                    .collect::<HashSet<&String>>()
+                    .len();
+                let active_athletes = team_week_athletes
+                    .get(&(team.clone(), *week))
+                    .map(|athletes| athletes.len())
+                    .unwrap_or(0);
+
+                team_weeks.push(TeamParticipationWeek {
+                    week_start: *week,
+                    active_athletes,
+                    roster_size,
+                    participation_rate: if roster_size == 0 { 0.0 } else { (active_athletes as f64 / roster_size as f64).min(1.0) },
+                });
+            }
+
+            let average_participation_rate = if team_weeks.is_empty() {
+                0.0
+            } else {
+                team_weeks.iter().map(|week| week.participation_rate).sum::<f64>() / team_weeks.len() as f64
+            };
+            teams.push(TeamParticipation {
+                team: team.clone(),
+                weeks: team_weeks,
+                average_participation_rate,
+            });
         }
 
-        Ok(athlete_teams)
+        Ok(CompetitionConsistency {
+            competition_id: competition.id.clone(),
+            start: start_date,
+            end: end_date,
+            athletes,
+            teams,
+        })
     }
 
-    // Hard coding team stat dates for now - club competition stats December 29th.
-    fn get_team_stat_dates(&self) -> Result<(DateTime<Utc>, DateTime<Utc>), ApiError> {
-        let start_date_naive = chrono::NaiveDate::from_ymd_opt(2025, 12, 1)
-            .ok_or_else(|| ApiError::InternalConversionError("Invalid start date".to_string()))?
-            .and_hms_opt(0, 0, 0)
-            .ok_or_else(|| ApiError::InternalConversionError("Invalid start time".to_string()))?;
-        let start_date_pacific = Los_Angeles.from_local_datetime(&start_date_naive).single()
-            .ok_or_else(|| ApiError::InternalConversionError("Invalid start date time".to_string()))?;
-        let start_date_utc = start_date_pacific.with_timezone(&Utc);
+    /// Explain how many points an activity earns in a competition
+    pub async fn explain_activity_points(&self, competition_id: &str, activity_id: &str) -> Result<PointsBreakdown, ApiError> {
+        let competition = self.db.read_competition(competition_id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No competition found with id: {}", competition_id)))?;
+        let activity = self.db.get_activity(activity_id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No activity found with id: {}", activity_id)))?;
 
-        let end_date_utc = Utc::now();
+        if let Some(reason) = competition.get_ineligibility(&activity) {
+            return Ok(PointsBreakdown::ineligible(&activity, reason));
+        }
+        // Same team filter as team stats: only athletes on one of the competition's teams at the time score
+        let reason = match self.db.get_activity_team(activity_id).await? {
+            None => Some("Activity isn't attributed to a roster athlete".to_string()),
+            Some(team) if !competition.teams.contains(&Team::normalize_id(&team)) => Some(format!("Team {} is not part of this competition", team)),
+            Some(_) if activity.distance.is_none() => Some("Activity has no distance".to_string()),
+            Some(_) => None,
+        };
+        if let Some(reason) = reason {
+            return Ok(PointsBreakdown::ineligible(&activity, reason));
+        }
+        Ok(competition.scoring.score(&activity))
+    }
 
-        Ok((start_date_utc, end_date_utc))
+    pub fn valid_activity(&self, activity: &BullSharkActivity, competition: &Competition) -> bool {
+        if let Some(sport_type) = &activity.sport_type {
+            if !competition.sport_types.contains(sport_type) {
+                return false;
+            }
+        } else {
+            return false; 
+        }
+        true
     }
 
-    fn get_start_of_week_for_activity(&self, activity: &BullSharkActivity) -> NaiveDateTime {
-        let activity_date = activity.date;
+    fn get_start_of_week_for_activity(&self, activity: &BullSharkActivity, timezone: &Tz) -> NaiveDateTime {
+        let activity_date = activity.date.with_timezone(timezone);
         let activity_date_naive = activity_date.naive_local();
         let days_since_monday = activity_date_naive.weekday().num_days_from_monday();
-        let start_of_week = activity_date_naive.date()
+        activity_date_naive.date()
             .and_hms_opt(0, 0, 0)
             .unwrap()
-            - Duration::days(days_since_monday as i64);
-        start_of_week
+            - Duration::days(days_since_monday as i64)
     }
 
     fn convert_weekly_map_to_vec(&self, weekly_map: HashMap<NaiveDateTime, WeekData>) -> Result<Vec<WeekData>, ApiError> {
@@ -249,7 +844,7 @@ impl ActivityController {
         let mut weekly_vec: Vec<(NaiveDateTime, WeekData)> = weekly_map
             .into_iter()
             .collect::<Vec<(NaiveDateTime, WeekData)>>();
-        weekly_vec.sort_by(|a, b| a.0.cmp(&b.0));
+        weekly_vec.sort_by_key(|a| a.0);
 
         let week_data_vec = weekly_vec
             .into_iter()
@@ -0,0 +1,152 @@
+use std::{collections::{BTreeSet, HashMap}, sync::Arc};
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::America::Los_Angeles;
+
+use crate::{error::ApiError, models::{athlete_profile::{AthleteProfile, AthleteProfileQuery, AthleteTotalsSummary, AthleteWeek, LongestRun, SportBreakdown}, bullshark::BullSharkActivity, effort::EffortTotals, streak::StreakSummary}, services::database::Database, utils::time_utils};
+
+const DEFAULT_PROFILE_WEEKS: u32 = 12;
+const MAX_PROFILE_WEEKS: u32 = 104;
+const DEFAULT_RECENT_ACTIVITIES: u32 = 10;
+const MAX_RECENT_ACTIVITIES: u32 = 100;
+
+pub struct AthleteController {
+    db: Arc<Database>,
+}
+
+impl AthleteController {
+    pub fn new(db: Arc<Database>) -> Self {
+        AthleteController { db }
+    }
+
+    pub async fn get_profile(&self, athlete_id: &str, query: AthleteProfileQuery) -> Result<AthleteProfile, ApiError> {
+        let athlete = self.db.read_athlete(athlete_id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No athlete found with id: {}", athlete_id)))?;
+
+        // Newest first
+        let activities = self.db.get_activities_for_athlete(athlete_id).await?;
+        println!("[ATHLETE_CONTROLLER] get_profile: Building profile for '{}' from {} activities", athlete.name, activities.len());
+
+        let weeks = query.weeks.unwrap_or(DEFAULT_PROFILE_WEEKS).clamp(1, MAX_PROFILE_WEEKS);
+        let recent = query.recent.unwrap_or(DEFAULT_RECENT_ACTIVITIES).min(MAX_RECENT_ACTIVITIES);
+
+        let totals = Self::build_totals(&activities);
+        let weekly = Self::build_weekly_history(&activities, weeks)?;
+        let sports = Self::build_sport_breakdown(&activities);
+        let streaks = Self::build_streaks(&activities);
+        let recent_activities = activities.into_iter().take(recent as usize).collect();
+
+        Ok(AthleteProfile {
+            athlete,
+            totals,
+            weekly,
+            sports,
+            streaks,
+            recent_activities,
+        })
+    }
+
+    fn build_totals(activities: &[BullSharkActivity]) -> AthleteTotalsSummary {
+        let mut totals = AthleteTotalsSummary::default();
+        let mut runs = EffortTotals::default();
+
+        for activity in activities {
+            let distance = activity.distance.unwrap_or(0.0);
+            totals.activity_count += 1;
+            totals.distance_km += distance / 1000.0;
+            totals.moving_time_seconds += activity.moving_time.unwrap_or(0);
+            totals.elevation_gain_meters += activity.total_elevation_gain.unwrap_or(0.0);
+
+            if !activity.is_run() {
+                continue;
+            }
+            runs.add(activity);
+
+            let is_longest = totals.longest_run
+                .as_ref()
+                .is_none_or(|longest| distance / 1000.0 > longest.distance_km);
+            if is_longest && distance > 0.0 {
+                totals.longest_run = Some(LongestRun {
+                    activity_id: activity.id.clone(),
+                    name: activity.name.clone(),
+                    date: activity.date,
+                    distance_km: distance / 1000.0,
+                });
+            }
+        }
+
+        totals.run_metrics = runs.get_metrics();
+        totals.average_pace_seconds_per_km = totals.run_metrics.pace_seconds_per_km;
+        totals
+    }
+
+    /// The last `weeks` weeks up to and including this one
+    fn build_weekly_history(activities: &[BullSharkActivity], weeks: u32) -> Result<Vec<AthleteWeek>, ApiError> {
+        let (current_week_start, _) = time_utils::get_week_window(Utc::now())?;
+
+        let mut history: Vec<AthleteWeek> = Vec::with_capacity(weeks as usize);
+        for offset in (0..weeks).rev() {
+            // Step back from the middle of the current week so DST changes can't skip a week
+            let reference = current_week_start + Duration::days(3) - Duration::weeks(offset as i64);
+            let (week_start, _) = time_utils::get_week_window(reference)?;
+            history.push(AthleteWeek {
+                week_start,
+                activity_count: 0,
+                distance_km: 0.0,
+                moving_time_seconds: 0,
+                elevation_gain_meters: 0.0,
+            });
+        }
+
+        let Some(first_week_start) = history.first().map(|week| week.week_start) else {
+            return Ok(history);
+        };
+        for activity in activities {
+            let date: DateTime<Utc> = activity.date.with_timezone(&Utc);
+            if date < first_week_start {
+                continue;
+            }
+            // Weeks are in order, so the activity belongs to the last week that started before it
+            let Some(week) = history.iter_mut().rev().find(|week| week.week_start <= date) else {
+                continue;
+            };
+            week.activity_count += 1;
+            week.distance_km += activity.distance.unwrap_or(0.0) / 1000.0;
+            week.moving_time_seconds += activity.moving_time.unwrap_or(0);
+            week.elevation_gain_meters += activity.total_elevation_gain.unwrap_or(0.0);
+        }
+
+        Ok(history)
+    }
+
+    /// Activity dates are already in Pacific time, so their calendar day is the local day
+    fn build_streaks(activities: &[BullSharkActivity]) -> StreakSummary {
+        let days: BTreeSet<NaiveDate> = activities
+            .iter()
+            .filter(|activity| activity.is_run())
+            .map(|activity| activity.date.date_naive())
+            .collect();
+        let today = Utc::now().with_timezone(&Los_Angeles).date_naive();
+        StreakSummary::from_days(&days, today)
+    }
+
+    fn build_sport_breakdown(activities: &[BullSharkActivity]) -> Vec<SportBreakdown> {
+        let mut sports: HashMap<String, SportBreakdown> = HashMap::new();
+        for activity in activities {
+            let sport_type = activity.sport_type.clone().unwrap_or_else(|| "Unknown".to_string());
+            let breakdown = sports.entry(sport_type.clone()).or_insert(SportBreakdown {
+                sport_type,
+                activity_count: 0,
+                distance_km: 0.0,
+                moving_time_seconds: 0,
+            });
+            breakdown.activity_count += 1;
+            breakdown.distance_km += activity.distance.unwrap_or(0.0) / 1000.0;
+            breakdown.moving_time_seconds += activity.moving_time.unwrap_or(0);
+        }
+
+        let mut sports: Vec<SportBreakdown> = sports.into_values().collect();
+        sports.sort_by(|a, b| b.distance_km.total_cmp(&a.distance_km).then_with(|| a.sport_type.cmp(&b.sport_type)));
+        sports
+    }
+}
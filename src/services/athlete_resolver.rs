@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use crate::{error::ApiError, models::athlete::{Athlete, AthleteAlias}, services::database::Database};
+
+/*
+Maps the raw athlete names on activities to roster athletes.
+Club activities only carry "First L." names, and members can rename themselves,
+so a name is resolved in order of:
+1. An alias an admin bound to the athlete
+2. The athlete's roster name
+3. The "First L." abbreviation of the roster name
+Names that match more than one athlete are left unresolved until an alias is bound.
+*/
+pub struct AthleteResolver {
+    athletes: HashMap<String, Athlete>,
+    aliases: HashMap<String, String>,
+    names: HashMap<String, Vec<String>>,
+    abbreviations: HashMap<String, Vec<String>>,
+}
+
+impl AthleteResolver {
+    pub async fn load(db: &Database) -> Result<Self, ApiError> {
+        let athletes = db.read_all_athletes().await?;
+        let aliases = db.read_all_athlete_aliases().await?;
+        Ok(Self::new(athletes, aliases))
+    }
+
+    pub fn new(athletes: Vec<Athlete>, aliases: Vec<AthleteAlias>) -> Self {
+        let mut names: HashMap<String, Vec<String>> = HashMap::new();
+        let mut abbreviations: HashMap<String, Vec<String>> = HashMap::new();
+        for athlete in &athletes {
+            names.entry(normalize_name(&athlete.name)).or_default().push(athlete.id.clone());
+            if let Some(abbreviation) = abbreviate_name(&athlete.name) {
+                abbreviations.entry(abbreviation).or_default().push(athlete.id.clone());
+            }
+        }
+
+        let aliases = aliases
+            .into_iter()
+            .map(|alias| (normalize_name(&alias.alias), alias.athlete_id))
+            .collect();
+
+        AthleteResolver {
+            athletes: athletes.into_iter().map(|athlete| (athlete.id.clone(), athlete)).collect(),
+            aliases,
+            names,
+            abbreviations,
+        }
+    }
+
+    pub fn resolve(&self, raw_name: &str) -> Option<&Athlete> {
+        let key = normalize_name(raw_name);
+        if let Some(athlete_id) = self.aliases.get(&key) {
+            return self.athletes.get(athlete_id);
+        }
+        // A full name match wins over an abbreviation, but an ambiguous one doesn't fall through
+        let athlete_ids = self.names.get(&key).or_else(|| self.abbreviations.get(&key));
+        match athlete_ids.map(Vec::as_slice) {
+            Some([athlete_id]) => self.athletes.get(athlete_id),
+            _ => None,
+        }
+    }
+
+    /// Every roster athlete a name could refer to, ignoring aliases
+    pub fn get_candidates(&self, raw_name: &str) -> Vec<String> {
+        let key = normalize_name(raw_name);
+        let mut candidates: Vec<String> = Vec::new();
+        for athlete_id in self.names.get(&key).into_iter().chain(self.abbreviations.get(&key)).flatten() {
+            if !candidates.contains(athlete_id) {
+                candidates.push(athlete_id.clone());
+            }
+        }
+        candidates
+    }
+}
+
+/// Lowercase with single spaces, so "Alex  S." and "alex s." match
+pub fn normalize_name(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// "Alex Smith" -> ("Alex", "S."), the first and last name the club feed returns
+pub fn get_club_name_parts(name: &str) -> Option<(String, String)> {
+    let (first_name, last_name) = name.trim().rsplit_once(char::is_whitespace)?;
+    let initial = last_name.chars().next()?;
+    Some((first_name.trim_end().to_string(), format!("{}.", initial.to_uppercase())))
+}
+
+// "Alex Smith" -> "alex s.", the format the club feed uses
+fn abbreviate_name(name: &str) -> Option<String> {
+    let normalized = normalize_name(name);
+    let (first_name, last_name) = normalized.rsplit_once(' ')?;
+    let initial = last_name.chars().next()?;
+    Some(format!("{} {}.", first_name, initial))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_athlete(id: &str, name: &str) -> Athlete {
+        Athlete {
+            id: id.to_string(),
+            name: name.to_string(),
+            team: "sharks".to_string(),
+            event: "marathon".to_string(),
+            strava_id: None,
+        }
+    }
+
+    fn get_alias(alias: &str, athlete_id: &str) -> AthleteAlias {
+        AthleteAlias {
+            alias: alias.to_string(),
+            athlete_id: athlete_id.to_string(),
+        }
+    }
+
+    fn get_resolved_id(resolver: &AthleteResolver, raw_name: &str) -> Option<String> {
+        resolver.resolve(raw_name).map(|athlete| athlete.id.clone())
+    }
+
+    #[test]
+    fn resolves_full_names_and_abbreviations_ignoring_case_and_spacing() {
+        let resolver = AthleteResolver::new(vec![get_athlete("alex", "Alex Smith"), get_athlete("mary", "Mary Ann Jones")], vec![]);
+
+        assert_eq!(get_resolved_id(&resolver, "alex  SMITH"), Some("alex".to_string()));
+        assert_eq!(get_resolved_id(&resolver, "Alex S."), Some("alex".to_string()));
+        assert_eq!(get_resolved_id(&resolver, "Mary Ann J."), Some("mary".to_string()));
+        assert_eq!(get_resolved_id(&resolver, "Sam J."), None);
+    }
+
+    #[test]
+    fn alias_wins_over_names() {
+        let athletes = vec![get_athlete("alex", "Alex Smith"), get_athlete("sam", "Sam Jones")];
+        let resolver = AthleteResolver::new(athletes, vec![get_alias("Alex Smith", "sam"), get_alias("Speedy", "alex")]);
+
+        assert_eq!(get_resolved_id(&resolver, "alex smith"), Some("sam".to_string()));
+        assert_eq!(get_resolved_id(&resolver, "Speedy"), Some("alex".to_string()));
+        assert_eq!(get_resolved_id(&resolver, "Alex S."), Some("alex".to_string()));
+    }
+
+    #[test]
+    fn full_name_wins_over_abbreviation() {
+        // Someone whose roster name is literally another athlete's "First L." form
+        let resolver = AthleteResolver::new(vec![get_athlete("alex", "Alex Smith"), get_athlete("alex-s", "Alex S.")], vec![]);
+
+        assert_eq!(get_resolved_id(&resolver, "Alex S."), Some("alex-s".to_string()));
+        assert_eq!(resolver.get_candidates("Alex S."), vec!["alex-s".to_string(), "alex".to_string()]);
+    }
+
+    #[test]
+    fn ambiguous_abbreviation_is_unresolved_until_aliased() {
+        let athletes = vec![get_athlete("alex-smith", "Alex Smith"), get_athlete("alex-stone", "Alex Stone")];
+        let resolver = AthleteResolver::new(athletes.clone(), vec![]);
+
+        assert_eq!(get_resolved_id(&resolver, "Alex S."), None);
+        assert_eq!(resolver.get_candidates("Alex S."), vec!["alex-smith".to_string(), "alex-stone".to_string()]);
+
+        let resolver = AthleteResolver::new(athletes, vec![get_alias("Alex S.", "alex-stone")]);
+        assert_eq!(get_resolved_id(&resolver, "Alex S."), Some("alex-stone".to_string()));
+    }
+}
@@ -1,14 +1,25 @@
-use crate::{error::ApiError, models::oauth::StravaTokenResponse, services::database::Database};
+use crate::{error::ApiError, models::{invite::{CreateStravaInvite, StravaConnection, StravaInvite, StravaInviteLink}, oauth::StravaTokenResponse}, services::{database::Database, strava_http::StravaHttp}};
 use dashmap::DashMap;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use std::sync::Arc;
 use crate::models::oauth::StravaAuthToken;
 
+// Scopes needed to read each member's own activities, including private ones
+const ATHLETE_SCOPES: &str = "read,activity:read_all";
+// How long an athlete has to complete the Strava consent screen
+const OAUTH_STATE_TTL_SECONDS: i64 = 15 * 60;
+// How long an admin's invite link stays usable
+const STRAVA_INVITE_TTL_DAYS: i64 = 7;
+
 #[derive(Clone)]
 pub struct StravaConfig {
     pub client_id: String,
     pub client_secret: String,
     pub club_id: String,
     pub admin_id: String,
+    pub sync_max_pages: u32,
+    pub redirect_uri: Option<String>,
 }
 
 impl StravaConfig {
@@ -18,6 +29,11 @@ impl StravaConfig {
             client_secret: std::env::var("STRAVA_CLIENT_SECRET")?,
             club_id: std::env::var("STRAVA_CLUB_ID")?,
             admin_id: "admin".to_string(),
+            sync_max_pages: std::env::var("STRAVA_SYNC_MAX_PAGES")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(10),
+            redirect_uri: std::env::var("STRAVA_REDIRECT_URI").ok(),
         })
     }
 }
@@ -25,20 +41,30 @@ impl StravaConfig {
 pub struct AuthController {
     strava_config: StravaConfig,
     db: Arc<Database>,
+    http: Arc<StravaHttp>,
     token_cache: Arc<DashMap<String, StravaAuthToken>>,
 }
 
 impl AuthController {
-    pub fn new(config: StravaConfig, db: Arc<Database>) -> Self {
+    pub fn new(config: StravaConfig, db: Arc<Database>, http: Arc<StravaHttp>) -> Self {
         AuthController { 
             strava_config: config,
-            db: db,
+            db,
+            http,
             token_cache: Arc::new(DashMap::new()),
         }
     }
 
     pub fn get_club_id(&self) -> String {
-        return self.strava_config.club_id.to_string();
+        self.strava_config.club_id.to_string()
+    }
+
+    pub fn get_sync_max_pages(&self) -> u32 {
+        self.strava_config.sync_max_pages
+    }
+
+    pub fn get_http(&self) -> Arc<StravaHttp> {
+        self.http.clone()
     }
 
     pub async fn get_valid_auth_token(&self) -> Result<String, ApiError> {
@@ -48,7 +74,7 @@ impl AuthController {
     pub async fn get_valid_auth_token_for_user(&self, user_id: &str) -> Result<String, ApiError> {
         if let Some(cached_token) = self.token_cache.get(user_id) {
             if !cached_token.is_expired() {
-                println!("[AUTH] Using cached token for user {}", user_id.to_string());
+                println!("[AUTH] Using cached token for user {}", user_id);
                 return Ok(cached_token.access_token.to_string());
             }
             drop(cached_token);
@@ -80,22 +106,16 @@ impl AuthController {
 
     async fn refresh_token(&self, old_token: &StravaAuthToken) -> Result<StravaAuthToken, ApiError> {
         println!("[AUTH] refresh_token: Starting token refresh request to Strava API");
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(15))
-            .build()
-            .map_err(|e| ApiError::ExternalAPIError(format!("Failed to build HTTP client: {}", e)))?;
         println!("[AUTH] refresh_token: Sending POST request to Strava OAuth endpoint");
-        let response = client
+        let request = self.http.client()
             .post("https://www.strava.com/oauth/token")
             .form(&[
                 ("client_id", self.strava_config.client_id.as_str()),
                 ("client_secret", self.strava_config.client_secret.as_str()),
                 ("grant_type", "refresh_token"),
                 ("refresh_token", old_token.refresh_token.as_str()),
-            ])
-            .send()
-            .await
-            .map_err(|e| ApiError::ExternalAPIError(format!("Strava API request failed: {}", e)))?;
+            ]);
+        let response = self.http.send(request).await?;
 
         println!("[AUTH] refresh_token: Received response from Strava, status: {}", response.status());
         if !response.status().is_success() {
@@ -117,6 +137,157 @@ impl AuthController {
         Ok(StravaAuthToken::new(old_token.id.clone(), token_response))
     }
 
+    /// Issue a one-time link for a roster athlete to connect Strava. Admin only.
+    pub async fn create_invite(&self, input: CreateStravaInvite) -> Result<StravaInviteLink, ApiError> {
+        self.db.read_athlete(&input.athlete_id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No athlete found with id: {}", input.athlete_id)))?;
+
+        let invite = StravaInvite {
+            token: uuid::Uuid::new_v4().simple().to_string(),
+            athlete_id: input.athlete_id,
+            replace_strava_id: input.replace_strava_id,
+            expires_at: chrono::Utc::now() + chrono::Duration::days(STRAVA_INVITE_TTL_DAYS),
+            used_at: None,
+        };
+        self.db.insert_strava_invite(&invite).await?;
+        println!("[AUTH] create_invite: Invite issued for athlete '{}'", invite.athlete_id);
+
+        let login_path = format!("/auth/strava/login?invite={}", invite.token);
+        Ok(StravaInviteLink { invite, login_path })
+    }
+
+    /// Build the Strava consent URL for an open invite. The invite token travels through
+    /// Strava in a signed `state` so the callback can link the token to the right row.
+    pub async fn build_authorize_url(&self, invite_token: &str) -> Result<String, ApiError> {
+        let redirect_uri = self.strava_config.redirect_uri
+            .as_ref()
+            .ok_or_else(|| ApiError::StartupError("STRAVA_REDIRECT_URI is not configured".to_string()))?;
+
+        self.db.read_open_strava_invite(invite_token).await?
+            .ok_or_else(|| ApiError::Unauthorized("This invite is invalid, used or expired. Ask an admin for a new one".to_string()))?;
+
+        let state = self.sign_state(invite_token, chrono::Utc::now().timestamp())?;
+        let url = reqwest::Url::parse_with_params(
+            "https://www.strava.com/oauth/authorize",
+            &[
+                ("client_id", self.strava_config.client_id.as_str()),
+                ("redirect_uri", redirect_uri.as_str()),
+                ("response_type", "code"),
+                ("approval_prompt", "auto"),
+                ("scope", ATHLETE_SCOPES),
+                ("state", state.as_str()),
+            ],
+        )
+        .map_err(|e| ApiError::InternalConversionError(format!("Failed to build Strava authorize URL: {}", e)))?;
+
+        Ok(url.to_string())
+    }
+
+    /// Exchange an authorization code for a token, use up the invite named in `state`,
+    /// link the Strava id to the invited athlete, and store the token under that Strava id.
+    /// The invite is only used up once the link is allowed.
+    pub async fn connect_athlete(&self, code: &str, state: &str, scope: &str) -> Result<StravaConnection, ApiError> {
+        let invite_token = self.verify_state(state)?;
+
+        if !scope.split(',').any(|s| s == "activity:read" || s == "activity:read_all") {
+            return Err(ApiError::BadRequest("Strava activity access was not granted".to_string()));
+        }
+
+        // Validate everything before the invite is used up, so a refused connect can be retried
+        let invalid_invite = || ApiError::Unauthorized("This invite is invalid, used or expired. Ask an admin for a new one".to_string());
+        let invite = self.db.read_open_strava_invite(&invite_token).await?
+            .ok_or_else(invalid_invite)?;
+        let athlete = self.db.read_athlete(&invite.athlete_id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No athlete found with id: {}", invite.athlete_id)))?;
+
+        println!("[AUTH] connect_athlete: Exchanging authorization code for an invite");
+        let request = self.http.client()
+            .post("https://www.strava.com/oauth/token")
+            .form(&[
+                ("client_id", self.strava_config.client_id.as_str()),
+                ("client_secret", self.strava_config.client_secret.as_str()),
+                ("grant_type", "authorization_code"),
+                ("code", code),
+            ]);
+        let response = self.http.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            eprintln!("[AUTH] connect_athlete: ERROR - Strava returned non-success status {}: {}", status, error_text);
+            return Err(ApiError::ExternalAPIError(
+                format!("Strava authorization code exchange failed ({}): {}", status, error_text)
+            ));
+        }
+
+        let token_response: StravaTokenResponse = response
+            .json()
+            .await
+            .map_err(|e| ApiError::ExternalAPIError(format!("Failed to parse Strava response: {}", e)))?;
+
+        let strava_id = token_response.athlete
+            .as_ref()
+            .map(|athlete| athlete.id.to_string())
+            .ok_or_else(|| ApiError::ExternalAPIError("Strava token response did not include the athlete".to_string()))?;
+
+        let replaced_strava_id = match &athlete.strava_id {
+            Some(existing) if *existing != strava_id => {
+                if !invite.replace_strava_id {
+                    println!("[AUTH] connect_athlete: Refused to replace Strava athlete '{}' on '{}' without admin confirmation", existing, athlete.id);
+                    return Err(ApiError::BadRequest("This athlete is already linked to a different Strava account. Ask an admin for an invite that replaces it".to_string()));
+                }
+                Some(existing.as_str())
+            }
+            _ => None,
+        };
+
+        self.db.claim_strava_invite(&invite.token, &strava_id, replaced_strava_id).await?
+            .ok_or_else(invalid_invite)?;
+        if let Some(replaced_strava_id) = replaced_strava_id {
+            self.token_cache.remove(replaced_strava_id);
+        }
+        self.store_token(StravaAuthToken::new(strava_id.clone(), token_response)).await?;
+        println!("[AUTH] connect_athlete: Athlete '{}' connected as Strava athlete '{}'", athlete.id, strava_id);
+
+        Ok(StravaConnection {
+            athlete_id: athlete.id,
+            strava_id,
+            scope: scope.to_string(),
+        })
+    }
+
+    // state = "{invite_token}.{issued_at}.{signature}", an HMAC keyed with the client secret
+    fn sign_state(&self, invite_token: &str, issued_at: i64) -> Result<String, ApiError> {
+        let signature = self.state_mac(invite_token, issued_at)?.finalize().into_bytes();
+        Ok(format!("{}.{}.{}", invite_token, issued_at, hex::encode(signature)))
+    }
+
+    fn verify_state(&self, state: &str) -> Result<String, ApiError> {
+        let invalid = || ApiError::Unauthorized("Invalid OAuth state".to_string());
+
+        let mut parts = state.rsplitn(3, '.');
+        let signature = parts.next().and_then(|p| hex::decode(p).ok()).ok_or_else(invalid)?;
+        let issued_at: i64 = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        let invite_token = parts.next().ok_or_else(invalid)?;
+
+        // verify_slice compares in constant time
+        self.state_mac(invite_token, issued_at)?
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+        if chrono::Utc::now().timestamp() - issued_at > OAUTH_STATE_TTL_SECONDS {
+            return Err(ApiError::Unauthorized("OAuth state has expired, please try connecting again".to_string()));
+        }
+
+        Ok(invite_token.to_string())
+    }
+
+    fn state_mac(&self, invite_token: &str, issued_at: i64) -> Result<Hmac<Sha256>, ApiError> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(self.strava_config.client_secret.as_bytes())
+            .map_err(|e| ApiError::InternalConversionError(format!("Failed to key OAuth state signature: {}", e)))?;
+        mac.update(format!("{}|{}", invite_token, issued_at).as_bytes());
+        Ok(mac)
+    }
+
     async fn store_token(&self, token: StravaAuthToken) -> Result<(), ApiError> {
         println!("[AUTH] store_token: Inserting token into cache for user '{}'", token.id);
         self.token_cache.insert(token.id.clone(), token.clone());
@@ -1,7 +1,13 @@
+use std::collections::HashSet;
+
+use futures_util::StreamExt;
 use sqlx::PgPool;
-use crate::{error::ApiError, models::{athlete::Athlete, bullshark::BullSharkActivity, oauth::StravaAuthToken}, utils::database_utils};
-use chrono::{DateTime, Utc, TimeZone, Offset};
-use chrono_tz::America::Los_Angeles;
+use tokio::sync::mpsc;
+use crate::{error::ApiError, models::{athlete::{Athlete, AthleteAlias, TeamMembership}, competition::Competition, event::RaceEvent, export::{ActivityExport, ExportQuery}, goal::Goal, leaderboard::AthleteTotals, pagination::{ActivityFilter, SortOrder}, team::Team, bullshark::{ActivityDeletion, ActivityUpsert, BullSharkActivity, DeletionReason, RosterActivity}, invite::StravaInvite, oauth::StravaAuthToken, workout::TeamWorkoutTypeVolume}, utils::database_utils};
+use chrono::{DateTime, FixedOffset, Utc};
+
+// Rows an export can run ahead of a slow client before the query waits
+const EXPORT_BUFFER_ROWS: usize = 256;
 
 pub struct Database {
     pool: PgPool,
@@ -32,8 +38,8 @@ impl Database {
         .bind(&token.id)
         .bind(&token.token_type)
         .bind(&token.access_token)
-        .bind(&token.expires_at)
-        .bind(&token.expires_in)
+        .bind(token.expires_at)
+        .bind(token.expires_in)
         .bind(&token.refresh_token)
         .execute(&self.pool)
         .await
@@ -60,6 +66,129 @@ impl Database {
         println!("[DB] get_auth_token: Query completed for user '{}', found: {}", id, result.is_some());
         Ok(result.map(database_utils::map_row_to_token))
     }
+
+    /// Which of `ids` have a stored auth token, in one query
+    pub async fn get_auth_token_ids(&self, ids: &[String]) -> Result<HashSet<String>, ApiError> {
+        use sqlx::Row;
+
+        if ids.is_empty() {
+            return Ok(HashSet::new())
+        }
+
+        let rows = sqlx::query(
+            r#"
+            SELECT id
+            FROM strava_auth_tokens
+            WHERE id = ANY($1)
+            "#
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to look up auth tokens: {}", e)))?;
+
+        Ok(rows.into_iter().map(|row| row.get("id")).collect())
+    }
+
+    pub async fn insert_strava_invite(&self, invite: &StravaInvite) -> Result<(), ApiError> {
+        println!("[DB] insert_strava_invite: Creating invite for athlete '{}'", invite.athlete_id);
+        sqlx::query(
+            r#"
+            INSERT INTO strava_invites
+            (token, athlete_id, replace_strava_id, expires_at)
+            VALUES ($1, $2, $3, $4)
+            "#
+        )
+        .bind(&invite.token)
+        .bind(&invite.athlete_id)
+        .bind(invite.replace_strava_id)
+        .bind(invite.expires_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to create Strava invite: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// The invite if it hasn't been used or expired
+    pub async fn read_open_strava_invite(&self, token: &str) -> Result<Option<StravaInvite>, ApiError> {
+        let row = sqlx::query(
+            r#"
+            SELECT token, athlete_id, replace_strava_id, expires_at, used_at
+            FROM strava_invites
+            WHERE token = $1 AND used_at IS NULL AND expires_at > NOW()
+            "#
+        )
+        .bind(token)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch Strava invite: {}", e)))?;
+
+        Ok(row.map(database_utils::map_row_to_strava_invite))
+    }
+
+    /// Marks the invite used and links its athlete to `strava_id` in one transaction,
+    /// dropping the stored token of the Strava account it replaces. Returns None, changing nothing,
+    /// if the invite was used or expired in the meantime, so only one of two concurrent callbacks wins.
+    pub async fn claim_strava_invite(&self, token: &str, strava_id: &str, replaced_strava_id: Option<&str>) -> Result<Option<StravaInvite>, ApiError> {
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+
+        let row = sqlx::query(
+            r#"
+            UPDATE strava_invites
+            SET used_at = NOW()
+            WHERE token = $1 AND used_at IS NULL AND expires_at > NOW()
+            RETURNING token, athlete_id, replace_strava_id, expires_at, used_at
+            "#
+        )
+        .bind(token)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to claim Strava invite: {}", e)))?;
+
+        let Some(invite) = row.map(database_utils::map_row_to_strava_invite) else {
+            return Ok(None)
+        };
+
+        println!("[DB] claim_strava_invite: Linking athlete '{}' to Strava athlete '{}'", invite.athlete_id, strava_id);
+        let result = sqlx::query(
+            r#"
+            UPDATE athletes
+            SET strava_id = $2
+            WHERE id = $1
+            "#
+        )
+        .bind(&invite.athlete_id)
+        .bind(strava_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            // athletes.strava_id is UNIQUE
+            Some(db_error) if db_error.is_unique_violation() => ApiError::BadRequest(format!("Strava athlete '{}' is already linked to another roster athlete", strava_id)),
+            _ => ApiError::DatabaseError(format!("Failed to link athlete to Strava: {}", e)),
+        })?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::NotFound(format!("No athlete found with id: {}", invite.athlete_id)));
+        }
+
+        if let Some(replaced_strava_id) = replaced_strava_id {
+            println!("[DB] claim_strava_invite: Deleting the auth token of replaced Strava athlete '{}'", replaced_strava_id);
+            sqlx::query("DELETE FROM strava_auth_tokens WHERE id = $1")
+                .bind(replaced_strava_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| ApiError::DatabaseError(format!("Failed to delete replaced auth token: {}", e)))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Failed to commit Strava invite claim: {}", e)))?;
+
+        Ok(Some(invite))
+    }
     // MARK: Auth Tokens End
 
 
@@ -72,22 +201,27 @@ impl Database {
             r#"
             INSERT INTO bullshark_activities 
             (id, date, resource_state, name, distance, moving_time, elapsed_time, 
-            total_elevation_gain, sport_type, workout_type, device_name, athlete_name)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            total_elevation_gain, sport_type, workout_type, device_name, athlete_name,
+            first_seen_at, date_confidence, strava_activity_id, athlete_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
             "#
         )
         .bind(&activity.id)
-        .bind(&activity.date)
-        .bind(&activity.resource_state)
+        .bind(activity.date)
+        .bind(activity.resource_state)
         .bind(&activity.name)
-        .bind(&activity.distance)
-        .bind(&activity.moving_time)
-        .bind(&activity.elapsed_time)
-        .bind(&activity.total_elevation_gain)
+        .bind(activity.distance)
+        .bind(activity.moving_time)
+        .bind(activity.elapsed_time)
+        .bind(activity.total_elevation_gain)
         .bind(&activity.sport_type)
-        .bind(&activity.workout_type)
+        .bind(activity.workout_type)
         .bind(&activity.device_name)
         .bind(&activity.athlete_name)
+        .bind(activity.first_seen_at)
+        .bind(activity.date_confidence.as_str())
+        .bind(activity.strava_activity_id)
+        .bind(&activity.athlete_id)
         .execute(&self.pool)
         .await
         .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
@@ -114,16 +248,22 @@ impl Database {
         let workout_types: Vec<Option<i64>> = activities.iter().map(|a| a.workout_type).collect();
         let device_names: Vec<Option<String>> = activities.iter().map(|a| a.device_name.clone()).collect();
         let athlete_names: Vec<Option<String>> = activities.iter().map(|a| a.athlete_name.clone()).collect();
+        let first_seen_ats: Vec<DateTime<Utc>> = activities.iter().map(|a| a.first_seen_at.with_timezone(&Utc)).collect();
+        let date_confidences: Vec<String> = activities.iter().map(|a| a.date_confidence.as_str().to_string()).collect();
+        let strava_activity_ids: Vec<Option<i64>> = activities.iter().map(|a| a.strava_activity_id).collect();
+        let athlete_ids: Vec<Option<String>> = activities.iter().map(|a| a.athlete_id.clone()).collect();
 
         // Use PostgreSQL UNNEST to insert all rows in a single query
         let result = sqlx::query(
             r#"
             INSERT INTO bullshark_activities
             (id, date, resource_state, name, distance, moving_time, elapsed_time,
-            total_elevation_gain, sport_type, workout_type, device_name, athlete_name)
+            total_elevation_gain, sport_type, workout_type, device_name, athlete_name,
+            first_seen_at, date_confidence, strava_activity_id, athlete_id)
             SELECT * FROM UNNEST($1::text[], $2::timestamptz[], $3::bigint[], $4::text[], $5::float8[],
                                  $6::bigint[], $7::bigint[], $8::float8[], $9::text[], $10::bigint[],
-                                 $11::text[], $12::text[])
+                                 $11::text[], $12::text[], $13::timestamptz[], $14::text[], $15::bigint[],
+                                 $16::text[])
             ON CONFLICT (id) DO NOTHING
             "#
         )
@@ -139,6 +279,10 @@ impl Database {
         .bind(&workout_types)
         .bind(&device_names)
         .bind(&athlete_names)
+        .bind(&first_seen_ats)
+        .bind(&date_confidences)
+        .bind(&strava_activity_ids)
+        .bind(&athlete_ids)
         .execute(&self.pool)
         .await
         .map_err(|e| ApiError::DatabaseError(format!("Failed to batch insert activities: {}", e)))?;
@@ -148,201 +292,1491 @@ impl Database {
         Ok(())
     }
 
-    pub async fn get_all_activities(&self) -> Result<Vec<BullSharkActivity>, ApiError> {
+    /// Insert or refresh an activity we received directly from Strava (e.g. via webhook).
+    /// An edit changes the hash id, so any older row for the same Strava activity is replaced.
+    pub async fn upsert_activity(&self, activity: &BullSharkActivity) -> Result<ActivityUpsert, ApiError> {
+        use sqlx::Row;
+
+        println!("[DB] upsert_activity: Upserting activity '{}' (Strava id {:?})", activity.id, activity.strava_activity_id);
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+
+        let existed = sqlx::query("SELECT 1 FROM bullshark_activities WHERE id = $1 AND deleted_at IS NULL")
+            .bind(&activity.id)
+            .fetch_optional(&mut *tx)
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Failed to check for existing activity: {}", e)))?
+            .is_some();
+
+        let mut replaced_ids: Vec<String> = Vec::new();
+        if let Some(strava_activity_id) = activity.strava_activity_id {
+            // Release the Strava id from the old version so the new row can claim it
+            let rows = sqlx::query(
+                r#"
+                UPDATE bullshark_activities
+                SET deleted_at = COALESCE(deleted_at, NOW()),
+                    deleted_reason = COALESCE(deleted_reason, $3),
+                    superseded_by = COALESCE(superseded_by, $2),
+                    strava_activity_id = NULL
+                WHERE strava_activity_id = $1 AND id <> $2
+                RETURNING id
+                "#
+            )
+            .bind(strava_activity_id)
+            .bind(&activity.id)
+            .bind(DeletionReason::Edited.as_str())
+            .fetch_all(&mut *tx)
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Failed to replace edited activity: {}", e)))?;
+            replaced_ids = rows.into_iter().map(|row| row.get("id")).collect();
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO bullshark_activities
+            (id, date, resource_state, name, distance, moving_time, elapsed_time,
+            total_elevation_gain, sport_type, workout_type, device_name, athlete_name,
+            first_seen_at, date_confidence, strava_activity_id, athlete_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            ON CONFLICT (id) DO UPDATE SET
+                date = EXCLUDED.date,
+                date_confidence = EXCLUDED.date_confidence,
+                resource_state = EXCLUDED.resource_state,
+                name = EXCLUDED.name,
+                total_elevation_gain = EXCLUDED.total_elevation_gain,
+                sport_type = EXCLUDED.sport_type,
+                workout_type = EXCLUDED.workout_type,
+                device_name = EXCLUDED.device_name,
+                strava_activity_id = EXCLUDED.strava_activity_id,
+                athlete_id = EXCLUDED.athlete_id,
+                deleted_at = NULL,
+                deleted_reason = NULL,
+                superseded_by = NULL
+            "#
+        )
+        .bind(&activity.id)
+        .bind(activity.date)
+        .bind(activity.resource_state)
+        .bind(&activity.name)
+        .bind(activity.distance)
+        .bind(activity.moving_time)
+        .bind(activity.elapsed_time)
+        .bind(activity.total_elevation_gain)
+        .bind(&activity.sport_type)
+        .bind(activity.workout_type)
+        .bind(&activity.device_name)
+        .bind(&activity.athlete_name)
+        .bind(activity.first_seen_at)
+        .bind(activity.date_confidence.as_str())
+        .bind(activity.strava_activity_id)
+        .bind(&activity.athlete_id)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to upsert activity: {}", e)))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Failed to commit activity upsert: {}", e)))?;
+        Ok(ActivityUpsert {
+            existed,
+            replaced_ids,
+        })
+    }
+
+    /// Returns the ids of the deleted activities
+    pub async fn soft_delete_activity_by_strava_id(&self, strava_activity_id: i64, athlete_id: &str) -> Result<Vec<String>, ApiError> {
         use sqlx::Row;
 
-        println!("[DB] get_all_activities: Starting query for all activities");
+        println!("[DB] soft_delete_activity_by_strava_id: Deleting activity with Strava id {} for athlete '{}'", strava_activity_id, athlete_id);
         let rows = sqlx::query(
             r#"
-            SELECT id, date, resource_state, name, distance, moving_time,
-                    elapsed_time, total_elevation_gain, sport_type, workout_type, device_name, athlete_name
-            FROM bullshark_activities
-            ORDER BY date DESC
+            UPDATE bullshark_activities
+            SET deleted_at = NOW(), deleted_reason = $2
+            WHERE strava_activity_id = $1 AND athlete_id = $3 AND deleted_at IS NULL
+            RETURNING id
             "#
         )
+        .bind(strava_activity_id)
+        .bind(DeletionReason::Deleted.as_str())
+        .bind(athlete_id)
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch activities: {}", e)))?;
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to delete activity: {}", e)))?;
 
-        let activities: Vec<BullSharkActivity> = rows.into_iter().map(|row| {
-            // Get timestamp from DB (stored as UTC)
-            let date_utc: DateTime<Utc> = row.get("date");
+        Ok(rows.into_iter().map(|row| row.get("id")).collect())
+    }
 
-            // Convert to Pacific timezone
-            let date_pacific_tz = Los_Angeles.from_utc_datetime(&date_utc.naive_utc());
-            // Convert to FixedOffset for serialization support
-            let date_pacific = date_pacific_tz.with_timezone(&date_pacific_tz.offset().fix());
+    /// A live club feed row for the same athlete and stats, which a webhook upsert should take over
+    pub async fn read_matching_club_activity_id(&self, athlete_id: &str, distance: f64, moving_time: i64, elapsed_time: i64) -> Result<Option<String>, ApiError> {
+        use sqlx::Row;
+        println!("[DB] read_matching_club_activity_id: Looking for a club activity from athlete '{}'", athlete_id);
+        let row = sqlx::query(
+            r#"
+            SELECT id FROM bullshark_activities
+            WHERE athlete_id = $1 AND distance = $2 AND moving_time = $3 AND elapsed_time = $4
+                AND strava_activity_id IS NULL AND deleted_at IS NULL
+            ORDER BY first_seen_at DESC
+            LIMIT 1
+            "#
+        )
+        .bind(athlete_id)
+        .bind(distance)
+        .bind(moving_time)
+        .bind(elapsed_time)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to read matching club activity: {}", e)))?;
 
-            BullSharkActivity {
-                id: row.get("id"),
-                date: date_pacific,
-                athlete_name: row.get("athlete_name"),
-                resource_state: row.get("resource_state"),
-                name: row.get("name"),
-                distance: row.get("distance"),
-                moving_time: row.get("moving_time"),
-                elapsed_time: row.get("elapsed_time"),
-                total_elevation_gain: row.get("total_elevation_gain"),
-                sport_type: row.get("sport_type"),
-                workout_type: row.get("workout_type"),
-                device_name: row.get("device_name"),
-            }
-        }).collect();
+        Ok(row.map(|row| row.get("id")))
+    }
 
-        println!("[DB] get_all_activities: Query completed, returned {} activities", activities.len());
-        Ok(activities)
+    pub async fn soft_delete_activities(&self, deletions: &[ActivityDeletion]) -> Result<(), ApiError> {
+        if deletions.is_empty() {
+            return Ok(())
+        }
+
+        let ids: Vec<String> = deletions.iter().map(|d| d.id.clone()).collect();
+        let reasons: Vec<String> = deletions.iter().map(|d| d.reason.as_str().to_string()).collect();
+        let superseded_by: Vec<Option<String>> = deletions.iter().map(|d| d.superseded_by.clone()).collect();
+
+        let result = sqlx::query(
+            r#"
+            UPDATE bullshark_activities AS a
+            SET deleted_at = NOW(), deleted_reason = d.reason, superseded_by = d.superseded_by
+            FROM UNNEST($1::text[], $2::text[], $3::text[]) AS d(id, reason, superseded_by)
+            WHERE a.id = d.id AND a.deleted_at IS NULL
+            "#
+        )
+        .bind(&ids)
+        .bind(&reasons)
+        .bind(&superseded_by)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to soft delete activities: {}", e)))?;
+
+        println!("[DB] soft_delete_activities: Soft deleted {} activities", result.rows_affected());
+        Ok(())
     }
 
-    pub async fn get_activities_from_window(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<BullSharkActivity>, ApiError> {
-        use sqlx::Row;
+    /// Undo a `removed` soft delete for activities that showed up in the club feed again
+    pub async fn restore_removed_activities(&self, ids: &[String]) -> Result<u64, ApiError> {
+        if ids.is_empty() {
+            return Ok(0)
+        }
+
+        let result = sqlx::query(
+            r#"
+            UPDATE bullshark_activities
+            SET deleted_at = NULL, deleted_reason = NULL, superseded_by = NULL
+            WHERE id = ANY($1) AND deleted_reason = $2
+            "#
+        )
+        .bind(ids)
+        .bind(DeletionReason::Removed.as_str())
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to restore removed activities: {}", e)))?;
+
+        Ok(result.rows_affected())
+    }
 
-        println!("[DB] get_activities_from_window: Starting query for activities between {:?} and {:?}", start, end);
+    /// Live activities first seen no earlier than `anchor_id`, other than the anchor, that are not in `seen_ids`.
+    /// The club feed is ordered by upload, so everything newer than the anchor should have been seen, and so
+    /// should the rest of the anchor's batch, since it shares the anchor's `first_seen_at`.
+    /// Webhook-delivered activities are skipped, since private activities never appear in the club feed.
+    pub async fn get_unseen_activities_since(&self, anchor_id: &str, seen_ids: &[String]) -> Result<Vec<BullSharkActivity>, ApiError> {
         let rows = sqlx::query(
             r#"
-            SELECT id, date, resource_state, name, distance, moving_time,
-                    elapsed_time, total_elevation_gain, sport_type, workout_type, device_name, athlete_name
+            SELECT id, strava_activity_id, date, first_seen_at, date_confidence, resource_state, name, distance, moving_time,
+                    elapsed_time, total_elevation_gain, sport_type, workout_type, device_name, athlete_name, athlete_id
             FROM bullshark_activities
-            WHERE date >= $1 AND date <= $2
-            ORDER BY date DESC
+            WHERE deleted_at IS NULL
+              AND strava_activity_id IS NULL
+              AND first_seen_at >= (SELECT first_seen_at FROM bullshark_activities WHERE id = $1)
+              AND id <> $1
+              AND NOT (id = ANY($2))
             "#
         )
-        .bind(start)
-        .bind(end)
+        .bind(anchor_id)
+        .bind(seen_ids)
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch activities from window: {}", e)))?;
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch unseen activities: {}", e)))?;
 
-        let activities: Vec<BullSharkActivity> = rows.into_iter().map(|row| {
-            // Get timestamp from DB (stored as UTC)
-            let date_utc: DateTime<Utc> = row.get("date");
-
-            // Convert to Pacific timezone
-            let date_pacific_tz = Los_Angeles.from_utc_datetime(&date_utc.naive_utc());
-            // Convert to FixedOffset for serialization support
-            let date_pacific = date_pacific_tz.with_timezone(&date_pacific_tz.offset().fix());
+        Ok(rows.into_iter().map(database_utils::map_row_to_activity).collect())
+    }
 
-            BullSharkActivity {
-                id: row.get("id"),
-                date: date_pacific,
-                athlete_name: row.get("athlete_name"),
-                resource_state: row.get("resource_state"),
-                name: row.get("name"),
-                distance: row.get("distance"),
-                moving_time: row.get("moving_time"),
-                elapsed_time: row.get("elapsed_time"),
-                total_elevation_gain: row.get("total_elevation_gain"),
-                sport_type: row.get("sport_type"),
-                workout_type: row.get("workout_type"),
-                device_name: row.get("device_name"),
-            }
-        }).collect();
+    pub async fn get_activity(&self, id: &str) -> Result<Option<BullSharkActivity>, ApiError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, strava_activity_id, date, first_seen_at, date_confidence, resource_state, name, distance, moving_time,
+                    elapsed_time, total_elevation_gain, sport_type, workout_type, device_name, athlete_name, athlete_id
+            FROM bullshark_activities
+            WHERE id = $1 AND deleted_at IS NULL
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch activity: {}", e)))?;
 
-        println!("[DB] get_activities_from_window: Query completed, returned {} activities", activities.len());
-        Ok(activities)
+        Ok(row.map(database_utils::map_row_to_activity))
     }
-    // MARK: Activities End
 
+    /// The team the activity's athlete was on at the time, None when it isn't attributed to a roster athlete
+    pub async fn get_activity_team(&self, id: &str) -> Result<Option<String>, ApiError> {
+        use sqlx::Row;
 
+        let row = sqlx::query(
+            r#"
+            SELECT COALESCE(m.team, LOWER(a.team)) AS team
+            FROM bullshark_activities b
+            JOIN athletes a ON a.id = b.athlete_id
+            LEFT JOIN team_memberships m ON m.athlete_id = b.athlete_id
+                AND b.date >= m.valid_from
+                AND (m.valid_to IS NULL OR b.date < m.valid_to)
+            WHERE b.id = $1 AND b.deleted_at IS NULL
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch activity team: {}", e)))?;
 
+        Ok(row.map(|row| row.get("team")))
+    }
 
+    pub async fn get_existing_activity_ids(&self, ids: &[String]) -> Result<HashSet<String>, ApiError> {
+        use sqlx::Row;
 
-    // MARK: Health Check
-    pub async fn health_check(&self) -> Result<(), ApiError> {
-        println!("[DB] health_check: Starting database health check");
-        sqlx::query("SELECT 1")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| ApiError::DatabaseError(format!("Health check failed: {}", e)))?;
+        if ids.is_empty() {
+            return Ok(HashSet::new())
+        }
 
-        println!("[DB] health_check: Health check completed successfully");
-        Ok(())
-    }
-    // MARK: Health Check End
+        let rows = sqlx::query(
+            r#"
+            SELECT id
+            FROM bullshark_activities
+            WHERE id = ANY($1)
+            "#
+        )
+        .bind(ids)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to look up existing activity ids: {}", e)))?;
 
+        Ok(rows.into_iter().map(|row| row.get("id")).collect())
+    }
 
+    /// When the last completed club sync started, whether or not it stored anything
+    pub async fn get_last_sync_at(&self) -> Result<Option<DateTime<Utc>>, ApiError> {
+        use sqlx::Row;
 
+        let row = sqlx::query(
+            r#"
+            SELECT MAX(started_at) AS latest
+            FROM sync_runs
+            "#
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch last sync time: {}", e)))?;
 
+        Ok(row.get("latest"))
+    }
 
-    // MARK: Athletes
-    pub async fn insert_athlete(&self, athlete: &Athlete) -> Result<(), ApiError> {
+    pub async fn insert_sync_run(&self, started_at: DateTime<Utc>, new_activity_count: usize) -> Result<(), ApiError> {
         sqlx::query(
             r#"
-            INSERT INTO athletes
-            (id, name, team, event)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO sync_runs (started_at, new_activity_count)
+            VALUES ($1, $2)
+            ON CONFLICT (started_at) DO NOTHING
             "#
         )
-        .bind(&athlete.id)
-        .bind(&athlete.name)
-        .bind(&athlete.team)
-        .bind(&athlete.event)
+        .bind(started_at)
+        .bind(new_activity_count as i32)
         .execute(&self.pool)
         .await
-        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to record sync run: {}", e)))?;
 
         Ok(())
     }
 
-    pub async fn insert_athletes(&self, athletes: &[Athlete]) -> Result<(), ApiError> {
-        if athletes.is_empty() {
-            println!("insert_athletes | received an athletes slice with 0 length, skipping batch operation");
-            return Ok(())
-        }
-
-        // Build arrays for each column
-        let ids: Vec<String> = athletes.iter().map(|a| a.id.clone()).collect();
-        let names: Vec<String> = athletes.iter().map(|a| a.name.clone()).collect();
-        let teams: Vec<String> = athletes.iter().map(|a| a.team.clone()).collect();
-        let events: Vec<String> = athletes.iter().map(|a| a.event.clone()).collect();
+    /// One page of activities matching the filter, plus one extra row when there is a next page.
+    /// The sort column comes from `ActivitySort`, never from user input, so it is safe to format into the query.
+    pub async fn get_activity_page(&self, filter: &ActivityFilter) -> Result<Vec<BullSharkActivity>, ApiError> {
+        let (sort_column, cursor_type) = filter.sort.get_sql();
+        let (direction, comparison) = match filter.order {
+            SortOrder::Desc => ("DESC", "<"),
+            SortOrder::Asc => ("ASC", ">"),
+        };
 
-        // Use PostgreSQL UNNEST to insert all rows in a single query
-        let result = sqlx::query(
+        let query = format!(
             r#"
-            INSERT INTO athletes
-            (id, name, team, event)
-            SELECT * FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[])
-            ON CONFLICT (id) DO NOTHING
+            SELECT b.id, b.strava_activity_id, b.date, b.first_seen_at, b.date_confidence, b.resource_state, b.name, b.distance, b.moving_time,
+                    b.elapsed_time, b.total_elevation_gain, b.sport_type, b.workout_type, b.device_name, b.athlete_name, b.athlete_id
+            FROM bullshark_activities b
+            LEFT JOIN athletes a ON a.id = b.athlete_id
+            LEFT JOIN team_memberships m ON m.athlete_id = b.athlete_id
+                AND b.date >= m.valid_from
+                AND (m.valid_to IS NULL OR b.date < m.valid_to)
+            WHERE b.deleted_at IS NULL
+              AND ($1::text IS NULL OR b.athlete_id = $1)
+              AND ($2::text IS NULL OR COALESCE(m.team, LOWER(a.team)) = $2)
+              AND ($3::text IS NULL OR b.sport_type = $3)
+              AND ($4::bigint[] IS NULL OR COALESCE(b.workout_type, 0) = ANY($4))
+              AND ($5::float8 IS NULL OR COALESCE(b.distance, 0) >= $5)
+              AND ($6::float8 IS NULL OR COALESCE(b.distance, 0) <= $6)
+              AND ($7::timestamptz IS NULL OR b.date >= $7)
+              AND ($8::timestamptz IS NULL OR b.date <= $8)
+              AND ($9::text IS NULL OR ({sort_column}, b.id) {comparison} ($9::{cursor_type}, $10))
+            ORDER BY {sort_column} {direction}, b.id {direction}
+            LIMIT $11
             "#
-        )
-        .bind(&ids)
-        .bind(&names)
-        .bind(&teams)
-        .bind(&events)
-        .execute(&self.pool)
-        .await
-        .map_err(|e| ApiError::DatabaseError(format!("Failed to batch insert athletes: {}", e)))?;
+        );
 
-        println!("Batch insert complete. Inserted {} new athletes.", result.rows_affected());
+        let rows = sqlx::query(&query)
+            .bind(&filter.athlete_id)
+            .bind(&filter.team)
+            .bind(&filter.sport_type)
+            .bind(&filter.workout_types)
+            .bind(filter.min_distance)
+            .bind(filter.max_distance)
+            .bind(filter.start)
+            .bind(filter.end)
+            .bind(filter.cursor.as_ref().map(|cursor| &cursor.value))
+            .bind(filter.cursor.as_ref().map(|cursor| &cursor.id))
+            // LIMIT NULL returns every row
+            .bind(filter.limit.map(|limit| limit as i64 + 1))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch activity page: {}", e)))?;
 
-        Ok(())
+        let activities: Vec<BullSharkActivity> = rows.into_iter().map(database_utils::map_row_to_activity).collect();
+
+        println!("[DB] get_activity_page: Query completed, returned {} activities sorted by {} {}", activities.len(), filter.sort.as_str(), filter.order.as_str());
+        Ok(activities)
     }
 
-    pub async fn read_all_athletes(&self) -> Result<Vec<Athlete>, ApiError> {
+    /// Live activities in the window that resolved to a roster athlete, with the athlete's name
+    /// and the team they were on when the activity happened
+    pub async fn get_roster_activities_from_window(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<RosterActivity>, ApiError> {
         use sqlx::Row;
 
-        println!("[DB] read_all_athletes: Starting query for all athletes");
+        println!("[DB] get_roster_activities_from_window: Starting query for activities between {:?} and {:?}", start, end);
         let rows = sqlx::query(
             r#"
-            SELECT id, name, team, event
-            FROM athletes
-            ORDER BY name ASC
+            SELECT b.id, b.strava_activity_id, b.date, b.first_seen_at, b.date_confidence, b.resource_state, b.name, b.distance,
+                    b.moving_time, b.elapsed_time, b.total_elevation_gain, b.sport_type, b.workout_type, b.device_name,
+                    b.athlete_name, b.athlete_id, a.name AS roster_name, COALESCE(m.team, LOWER(a.team)) AS roster_team
+            FROM bullshark_activities b
+            JOIN athletes a ON a.id = b.athlete_id
+            LEFT JOIN team_memberships m ON m.athlete_id = b.athlete_id
+                AND b.date >= m.valid_from
+                AND (m.valid_to IS NULL OR b.date < m.valid_to)
+            WHERE b.date >= $1 AND b.date <= $2 AND b.deleted_at IS NULL
+            ORDER BY b.date DESC
             "#
         )
+        .bind(start)
+        .bind(end)
         .fetch_all(&self.pool)
         .await
-        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch athletes: {}", e)))?;
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch roster activities from window: {}", e)))?;
 
-        let athletes: Vec<Athlete> = rows.into_iter().map(|row| {
-            Athlete {
-                id: row.get("id"),
-                name: row.get("name"),
-                team: row.get("team"),
-                event: row.get("event"),
+        let activities: Vec<RosterActivity> = rows.into_iter().map(|row| {
+            let athlete_name: String = row.get("roster_name");
+            let team: String = row.get("roster_team");
+            RosterActivity {
+                activity: database_utils::map_row_to_activity(row),
+                athlete_name,
+                team,
             }
         }).collect();
 
-        println!("[DB] read_all_athletes: Query completed, returned {} athletes", athletes.len());
-        Ok(athletes)
+        println!("[DB] get_roster_activities_from_window: Query completed, returned {} activities", activities.len());
+        Ok(activities)
     }
-    // MARK: Athletes End
-}
\ No newline at end of file
+
+    pub async fn get_activities_for_athlete(&self, athlete_id: &str) -> Result<Vec<BullSharkActivity>, ApiError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, strava_activity_id, date, first_seen_at, date_confidence, resource_state, name, distance, moving_time,
+                    elapsed_time, total_elevation_gain, sport_type, workout_type, device_name, athlete_name, athlete_id
+            FROM bullshark_activities
+            WHERE athlete_id = $1 AND deleted_at IS NULL
+            ORDER BY date DESC
+            "#
+        )
+        .bind(athlete_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch activities for athlete: {}", e)))?;
+
+        Ok(rows.into_iter().map(database_utils::map_row_to_activity).collect())
+    }
+
+    /// Activities attributed to any of `athlete_ids` within the window, oldest first
+    pub async fn get_activities_for_athletes(&self, athlete_ids: &[String], start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<BullSharkActivity>, ApiError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, strava_activity_id, date, first_seen_at, date_confidence, resource_state, name, distance, moving_time,
+                    elapsed_time, total_elevation_gain, sport_type, workout_type, device_name, athlete_name, athlete_id
+            FROM bullshark_activities
+            WHERE athlete_id = ANY($1) AND date >= $2 AND date <= $3 AND deleted_at IS NULL
+            ORDER BY date ASC
+            "#
+        )
+        .bind(athlete_ids)
+        .bind(start)
+        .bind(end)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch activities for athletes: {}", e)))?;
+
+        Ok(rows.into_iter().map(database_utils::map_row_to_activity).collect())
+    }
+
+    /// Points every club feed activity with one of `athlete_names` at the matching entry of `athlete_ids`.
+    /// Webhook activities are attributed to their owner and left alone.
+    pub async fn set_activity_athlete_ids(&self, athlete_names: &[String], athlete_ids: &[Option<String>]) -> Result<u64, ApiError> {
+        if athlete_names.is_empty() {
+            return Ok(0)
+        }
+
+        let result = sqlx::query(
+            r#"
+            UPDATE bullshark_activities b
+            SET athlete_id = u.athlete_id
+            FROM UNNEST($1::text[], $2::text[]) AS u(athlete_name, athlete_id)
+            WHERE b.athlete_name = u.athlete_name
+              AND b.strava_activity_id IS NULL
+              AND b.athlete_id IS DISTINCT FROM u.athlete_id
+            "#
+        )
+        .bind(athlete_names)
+        .bind(athlete_ids)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to set activity athlete ids: {}", e)))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Per-athlete totals over a window. `team` filters on the team the athlete was on at each
+    /// activity's date, while the returned team is the athlete's current one.
+    pub async fn get_athlete_totals(&self, start: DateTime<Utc>, end: DateTime<Utc>, team: Option<&str>, sport_types: Option<&[String]>, workout_types: Option<&[i64]>) -> Result<Vec<AthleteTotals>, ApiError> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT b.athlete_id, a.name AS athlete_name, a.team,
+                    COALESCE(SUM(b.distance), 0) AS distance,
+                    COALESCE(SUM(b.moving_time), 0)::bigint AS moving_time,
+                    COALESCE(SUM(b.elapsed_time), 0)::bigint AS elapsed_time,
+                    COALESCE(SUM(b.total_elevation_gain), 0) AS elevation_gain,
+                    COUNT(*) AS activity_count,
+                    COALESCE(MAX(b.distance), 0) AS longest_distance
+            FROM bullshark_activities b
+            JOIN athletes a ON a.id = b.athlete_id
+            LEFT JOIN team_memberships m ON m.athlete_id = b.athlete_id
+                AND b.date >= m.valid_from
+                AND (m.valid_to IS NULL OR b.date < m.valid_to)
+            WHERE b.date >= $1 AND b.date <= $2 AND b.deleted_at IS NULL
+              AND ($3::text IS NULL OR COALESCE(m.team, LOWER(a.team)) = $3)
+              AND ($4::text[] IS NULL OR b.sport_type = ANY($4))
+              AND ($5::bigint[] IS NULL OR b.workout_type = ANY($5))
+            GROUP BY b.athlete_id, a.name, a.team
+            "#
+        )
+        .bind(start)
+        .bind(end)
+        .bind(team)
+        .bind(sport_types)
+        .bind(workout_types)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch athlete totals: {}", e)))?;
+
+        let totals: Vec<AthleteTotals> = rows.into_iter().map(|row| {
+            AthleteTotals {
+                athlete_id: row.get("athlete_id"),
+                athlete_name: row.get("athlete_name"),
+                team: row.get("team"),
+                distance: row.get("distance"),
+                moving_time: row.get("moving_time"),
+                elapsed_time: row.get("elapsed_time"),
+                elevation_gain: row.get("elevation_gain"),
+                activity_count: row.get("activity_count"),
+                longest_distance: row.get("longest_distance"),
+            }
+        }).collect();
+
+        Ok(totals)
+    }
+
+    /// Attributed activities with one of `workout_types`, newest first, with the team the athlete was on at the time
+    pub async fn get_roster_activities_by_workout_type(&self, start: DateTime<Utc>, end: DateTime<Utc>, workout_types: &[i64], team: Option<&str>, athlete_id: Option<&str>) -> Result<Vec<RosterActivity>, ApiError> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT b.id, b.strava_activity_id, b.date, b.first_seen_at, b.date_confidence, b.resource_state, b.name, b.distance,
+                    b.moving_time, b.elapsed_time, b.total_elevation_gain, b.sport_type, b.workout_type, b.device_name,
+                    b.athlete_name, b.athlete_id, a.name AS roster_name, COALESCE(m.team, LOWER(a.team)) AS roster_team
+            FROM bullshark_activities b
+            JOIN athletes a ON a.id = b.athlete_id
+            LEFT JOIN team_memberships m ON m.athlete_id = b.athlete_id
+                AND b.date >= m.valid_from
+                AND (m.valid_to IS NULL OR b.date < m.valid_to)
+            WHERE b.date >= $1 AND b.date <= $2 AND b.deleted_at IS NULL
+              AND b.workout_type = ANY($3)
+              AND ($4::text IS NULL OR COALESCE(m.team, LOWER(a.team)) = $4)
+              AND ($5::text IS NULL OR b.athlete_id = $5)
+            ORDER BY b.date DESC
+            "#
+        )
+        .bind(start)
+        .bind(end)
+        .bind(workout_types)
+        .bind(team)
+        .bind(athlete_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch activities by workout type: {}", e)))?;
+
+        let activities: Vec<RosterActivity> = rows.into_iter().map(|row| {
+            let athlete_name: String = row.get("roster_name");
+            let team: String = row.get("roster_team");
+            RosterActivity {
+                activity: database_utils::map_row_to_activity(row),
+                athlete_name,
+                team,
+            }
+        }).collect();
+
+        Ok(activities)
+    }
+
+    /// Activity count, distance and moving time per team and raw workout type
+    pub async fn get_team_workout_volume(&self, start: DateTime<Utc>, end: DateTime<Utc>, sport_types: &[String]) -> Result<Vec<TeamWorkoutTypeVolume>, ApiError> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT COALESCE(m.team, LOWER(a.team)) AS team, b.workout_type,
+                    COUNT(*) AS activity_count,
+                    COALESCE(SUM(b.distance), 0) AS distance,
+                    COALESCE(SUM(b.moving_time), 0)::bigint AS moving_time
+            FROM bullshark_activities b
+            JOIN athletes a ON a.id = b.athlete_id
+            LEFT JOIN team_memberships m ON m.athlete_id = b.athlete_id
+                AND b.date >= m.valid_from
+                AND (m.valid_to IS NULL OR b.date < m.valid_to)
+            WHERE b.date >= $1 AND b.date <= $2 AND b.deleted_at IS NULL
+              AND b.sport_type = ANY($3)
+            GROUP BY 1, 2
+            "#
+        )
+        .bind(start)
+        .bind(end)
+        .bind(sport_types)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch team workout volume: {}", e)))?;
+
+        let volume = rows.into_iter().map(|row| {
+            TeamWorkoutTypeVolume {
+                team: row.get("team"),
+                workout_type: row.get("workout_type"),
+                activity_count: row.get("activity_count"),
+                distance: row.get("distance"),
+                moving_time: row.get("moving_time"),
+            }
+        }).collect();
+
+        Ok(volume)
+    }
+    // MARK: Activities End
+
+
+
+
+
+    // MARK: Health Check
+    pub async fn health_check(&self) -> Result<(), ApiError> {
+        println!("[DB] health_check: Starting database health check");
+        sqlx::query("SELECT 1")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Health check failed: {}", e)))?;
+
+        println!("[DB] health_check: Health check completed successfully");
+        Ok(())
+    }
+    // MARK: Health Check End
+
+
+
+
+
+    // MARK: Athletes
+    pub async fn insert_athlete(&self, athlete: &Athlete) -> Result<(), ApiError> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO athletes
+            (id, name, team, event)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (id) DO NOTHING
+            "#
+        )
+        .bind(&athlete.id)
+        .bind(&athlete.name)
+        .bind(&athlete.team)
+        .bind(&athlete.event)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::BadRequest(format!("An athlete with id {} already exists", athlete.id)));
+        }
+        self.set_current_memberships(std::slice::from_ref(&athlete.id), std::slice::from_ref(&athlete.team)).await?;
+        Ok(())
+    }
+
+    /// Inserts new athletes and updates the name, team and event of existing ones.
+    /// A linked `strava_id` is left alone.
+    pub async fn upsert_athletes(&self, athletes: &[Athlete]) -> Result<u64, ApiError> {
+        if athletes.is_empty() {
+            println!("upsert_athletes | received an athletes slice with 0 length, skipping batch operation");
+            return Ok(0)
+        }
+
+        // Build arrays for each column
+        let ids: Vec<String> = athletes.iter().map(|a| a.id.clone()).collect();
+        let names: Vec<String> = athletes.iter().map(|a| a.name.clone()).collect();
+        let teams: Vec<String> = athletes.iter().map(|a| a.team.clone()).collect();
+        let events: Vec<String> = athletes.iter().map(|a| a.event.clone()).collect();
+
+        // Use PostgreSQL UNNEST to insert all rows in a single query
+        let result = sqlx::query(
+            r#"
+            INSERT INTO athletes
+            (id, name, team, event)
+            SELECT * FROM UNNEST($1::text[], $2::text[], $3::text[], $4::text[])
+            ON CONFLICT (id) DO UPDATE SET
+                name = EXCLUDED.name,
+                team = EXCLUDED.team,
+                event = EXCLUDED.event
+            "#
+        )
+        .bind(&ids)
+        .bind(&names)
+        .bind(&teams)
+        .bind(&events)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to batch upsert athletes: {}", e)))?;
+
+        println!("Batch upsert complete. Upserted {} athletes.", result.rows_affected());
+        self.set_current_memberships(&ids, &teams).await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn update_athlete(&self, athlete: &Athlete) -> Result<(), ApiError> {
+        println!("[DB] update_athlete: Updating athlete '{}'", athlete.id);
+        let result = sqlx::query(
+            r#"
+            UPDATE athletes
+            SET name = $2, team = $3, event = $4
+            WHERE id = $1
+            "#
+        )
+        .bind(&athlete.id)
+        .bind(&athlete.name)
+        .bind(&athlete.team)
+        .bind(&athlete.event)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to update athlete: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::NotFound(format!("No athlete found with id: {}", athlete.id)));
+        }
+        self.set_current_memberships(std::slice::from_ref(&athlete.id), std::slice::from_ref(&athlete.team)).await?;
+        Ok(())
+    }
+
+    pub async fn delete_athlete(&self, id: &str) -> Result<(), ApiError> {
+        println!("[DB] delete_athlete: Deleting athlete '{}'", id);
+        let result = sqlx::query(
+            r#"
+            DELETE FROM athletes
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to delete athlete: {}", e)))?;
+
+        if result.rows_affected() == 0 {
+            return Err(ApiError::NotFound(format!("No athlete found with id: {}", id)));
+        }
+        Ok(())
+    }
+
+    pub async fn read_all_athletes(&self) -> Result<Vec<Athlete>, ApiError> {
+        println!("[DB] read_all_athletes: Starting query for all athletes");
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, team, event, strava_id
+            FROM athletes
+            ORDER BY name ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch athletes: {}", e)))?;
+
+        let athletes: Vec<Athlete> = rows.into_iter().map(database_utils::map_row_to_athlete).collect();
+
+        println!("[DB] read_all_athletes: Query completed, returned {} athletes", athletes.len());
+        Ok(athletes)
+    }
+
+    pub async fn read_athlete(&self, id: &str) -> Result<Option<Athlete>, ApiError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, name, team, event, strava_id
+            FROM athletes
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch athlete: {}", e)))?;
+
+        Ok(row.map(database_utils::map_row_to_athlete))
+    }
+
+    pub async fn read_athlete_by_strava_id(&self, strava_id: &str) -> Result<Option<Athlete>, ApiError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, name, team, event, strava_id
+            FROM athletes
+            WHERE strava_id = $1
+            "#
+        )
+        .bind(strava_id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch athlete by Strava id: {}", e)))?;
+
+        Ok(row.map(database_utils::map_row_to_athlete))
+    }
+
+    /// Roster edits correct the team of the current membership rather than recording a transfer.
+    /// Athletes without a membership yet are placed on their team from the start.
+    async fn set_current_memberships(&self, athlete_ids: &[String], teams: &[String]) -> Result<(), ApiError> {
+        sqlx::query(
+            r#"
+            INSERT INTO team_memberships
+            (athlete_id, team, valid_from)
+            SELECT id, LOWER(team), 'epoch'::timestamptz FROM UNNEST($1::text[], $2::text[]) AS u(id, team)
+            ON CONFLICT (athlete_id) WHERE valid_to IS NULL DO UPDATE SET
+                team = EXCLUDED.team
+            "#
+        )
+        .bind(athlete_ids)
+        .bind(teams)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to set team memberships: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub async fn read_team_memberships(&self, athlete_id: &str) -> Result<Vec<TeamMembership>, ApiError> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT team, valid_from, valid_to
+            FROM team_memberships
+            WHERE athlete_id = $1
+            ORDER BY valid_from ASC
+            "#
+        )
+        .bind(athlete_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch team memberships: {}", e)))?;
+
+        let memberships: Vec<TeamMembership> = rows.into_iter().map(|row| {
+            TeamMembership {
+                team: row.get("team"),
+                valid_from: row.get("valid_from"),
+                valid_to: row.get("valid_to"),
+            }
+        }).collect();
+
+        Ok(memberships)
+    }
+
+    /// Every membership, keyed by athlete id
+    pub async fn read_all_team_memberships(&self) -> Result<Vec<(String, TeamMembership)>, ApiError> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT athlete_id, team, valid_from, valid_to
+            FROM team_memberships
+            ORDER BY athlete_id, valid_from ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch team memberships: {}", e)))?;
+
+        let memberships = rows.into_iter().map(|row| {
+            (
+                row.get("athlete_id"),
+                TeamMembership {
+                    team: row.get("team"),
+                    valid_from: row.get("valid_from"),
+                    valid_to: row.get("valid_to"),
+                },
+            )
+        }).collect();
+
+        Ok(memberships)
+    }
+
+    /// Ends the athlete's current membership at `effective_at` and starts one on the team with id `team`
+    pub async fn transfer_athlete(&self, athlete_id: &str, team: &str, effective_at: DateTime<Utc>) -> Result<(), ApiError> {
+        use sqlx::Row;
+
+        println!("[DB] transfer_athlete: Moving athlete '{}' to '{}' from {}", athlete_id, team, effective_at);
+        let mut tx = self.pool.begin()
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Failed to start transaction: {}", e)))?;
+
+        let current = sqlx::query(
+            r#"
+            SELECT team, valid_from
+            FROM team_memberships
+            WHERE athlete_id = $1 AND valid_to IS NULL
+            FOR UPDATE
+            "#
+        )
+        .bind(athlete_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch current membership: {}", e)))?;
+
+        if let Some(current) = current {
+            let current_team: String = current.get("team");
+            let valid_from: DateTime<Utc> = current.get("valid_from");
+            if current_team == team {
+                return Err(ApiError::BadRequest(format!("Athlete is already on team {}", team)));
+            }
+            if effective_at <= valid_from {
+                return Err(ApiError::BadRequest(format!("Transfer must be after the current membership started ({})", valid_from)));
+            }
+
+            sqlx::query(
+                r#"
+                UPDATE team_memberships
+                SET valid_to = $2
+                WHERE athlete_id = $1 AND valid_to IS NULL
+                "#
+            )
+            .bind(athlete_id)
+            .bind(effective_at)
+            .execute(&mut *tx)
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Failed to close current membership: {}", e)))?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO team_memberships
+            (athlete_id, team, valid_from)
+            VALUES ($1, $2, $3)
+            "#
+        )
+        .bind(athlete_id)
+        .bind(team)
+        .bind(effective_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to insert team membership: {}", e)))?;
+
+        sqlx::query(
+            r#"
+            UPDATE athletes
+            SET team = $2
+            WHERE id = $1
+            "#
+        )
+        .bind(athlete_id)
+        .bind(team)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to update athlete team: {}", e)))?;
+
+        tx.commit()
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Failed to commit transfer: {}", e)))?;
+        Ok(())
+    }
+
+    pub async fn read_all_athlete_aliases(&self) -> Result<Vec<AthleteAlias>, ApiError> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT alias, athlete_id
+            FROM athlete_aliases
+            ORDER BY alias ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch athlete aliases: {}", e)))?;
+
+        let aliases: Vec<AthleteAlias> = rows.into_iter().map(|row| {
+            AthleteAlias {
+                alias: row.get("alias"),
+                athlete_id: row.get("athlete_id"),
+            }
+        }).collect();
+
+        Ok(aliases)
+    }
+
+    /// Binds an alias to an athlete, moving it if it was bound to someone else
+    pub async fn upsert_athlete_alias(&self, alias: &AthleteAlias) -> Result<(), ApiError> {
+        println!("[DB] upsert_athlete_alias: Binding '{}' to athlete '{}'", alias.alias, alias.athlete_id);
+        sqlx::query(
+            r#"
+            INSERT INTO athlete_aliases
+            (alias, athlete_id)
+            VALUES ($1, $2)
+            ON CONFLICT ((LOWER(alias))) DO UPDATE SET
+                alias = EXCLUDED.alias,
+                athlete_id = EXCLUDED.athlete_id
+            "#
+        )
+        .bind(&alias.alias)
+        .bind(&alias.athlete_id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to upsert athlete alias: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Every athlete name on live activities, with how often and how recently it was seen
+    pub async fn read_activity_athlete_names(&self) -> Result<Vec<(String, i64, DateTime<FixedOffset>)>, ApiError> {
+        use sqlx::Row;
+
+        let rows = sqlx::query(
+            r#"
+            SELECT athlete_name, COUNT(*) AS activity_count, MAX(date) AS last_seen
+            FROM bullshark_activities
+            WHERE deleted_at IS NULL AND athlete_name IS NOT NULL
+            GROUP BY athlete_name
+            ORDER BY last_seen DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch activity athlete names: {}", e)))?;
+
+        let names = rows.into_iter().map(|row| {
+            (
+                row.get("athlete_name"),
+                row.get("activity_count"),
+                database_utils::to_pacific(row.get("last_seen")),
+            )
+        }).collect();
+
+        Ok(names)
+    }
+    // MARK: Athletes End
+
+
+
+
+
+    // MARK: Competitions
+    pub async fn insert_competition(&self, competition: &Competition) -> Result<(), ApiError> {
+        println!("[DB] insert_competition: Inserting competition '{}'", competition.id);
+        let scoring = serde_json::to_string(&competition.scoring)
+            .map_err(|e| ApiError::InternalConversionError(format!("Failed to serialize scoring config: {}", e)))?;
+        sqlx::query(
+            r#"
+            INSERT INTO competitions
+            (id, name, start_date, end_date, timezone, teams, sport_types, scoring)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8::jsonb)
+            "#
+        )
+        .bind(&competition.id)
+        .bind(&competition.name)
+        .bind(competition.start_date)
+        .bind(competition.end_date)
+        .bind(&competition.timezone)
+        .bind(&competition.teams)
+        .bind(&competition.sport_types)
+        .bind(&scoring)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to insert competition: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub async fn read_all_competitions(&self) -> Result<Vec<Competition>, ApiError> {
+        println!("[DB] read_all_competitions: Starting query for all competitions");
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, start_date, end_date, timezone, teams, sport_types, scoring::text AS scoring
+            FROM competitions
+            ORDER BY start_date DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch competitions: {}", e)))?;
+
+        let competitions: Vec<Competition> = rows.into_iter().map(database_utils::map_row_to_competition).collect();
+
+        println!("[DB] read_all_competitions: Query completed, returned {} competitions", competitions.len());
+        Ok(competitions)
+    }
+
+    pub async fn read_competition(&self, id: &str) -> Result<Option<Competition>, ApiError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, name, start_date, end_date, timezone, teams, sport_types, scoring::text AS scoring
+            FROM competitions
+            WHERE id = $1
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch competition: {}", e)))?;
+
+        Ok(row.map(database_utils::map_row_to_competition))
+    }
+
+    /// The most recently started competition, used by the legacy /team_stats route
+    pub async fn read_current_competition(&self) -> Result<Option<Competition>, ApiError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, name, start_date, end_date, timezone, teams, sport_types, scoring::text AS scoring
+            FROM competitions
+            WHERE start_date <= NOW()
+            ORDER BY start_date DESC
+            LIMIT 1
+            "#
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch current competition: {}", e)))?;
+
+        Ok(row.map(database_utils::map_row_to_competition))
+    }
+    // MARK: Competitions End
+
+
+
+
+
+    // MARK: Teams
+    pub async fn upsert_team(&self, team: &Team) -> Result<(), ApiError> {
+        println!("[DB] upsert_team: Upserting team '{}'", team.id);
+        sqlx::query(
+            r#"
+            INSERT INTO teams
+            (id, display_name, color)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (id) DO UPDATE SET
+                display_name = EXCLUDED.display_name,
+                color = EXCLUDED.color
+            "#
+        )
+        .bind(&team.id)
+        .bind(&team.display_name)
+        .bind(&team.color)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to upsert team: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub async fn read_all_teams(&self) -> Result<Vec<Team>, ApiError> {
+        use sqlx::Row;
+
+        println!("[DB] read_all_teams: Starting query for all teams");
+        let rows = sqlx::query(
+            r#"
+            SELECT id, display_name, color
+            FROM teams
+            ORDER BY id ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch teams: {}", e)))?;
+
+        let teams: Vec<Team> = rows.into_iter().map(|row| {
+            Team {
+                id: row.get("id"),
+                display_name: row.get("display_name"),
+                color: row.get("color"),
+            }
+        }).collect();
+
+        println!("[DB] read_all_teams: Query completed, returned {} teams", teams.len());
+        Ok(teams)
+    }
+    // MARK: Teams End
+
+
+
+
+
+    // MARK: Exports
+    /*
+    Exports run the query on a spawned task and hand rows over a bounded channel,
+    so only a few hundred rows are held at once however large the window is.
+    The task stops as soon as the receiver is dropped, e.g. when the client disconnects.
+    */
+
+    /// Live activities in the window, oldest first, with the team the athlete was on at the time
+    pub fn stream_activities(&self, query: &ExportQuery, start: Option<DateTime<Utc>>, end: Option<DateTime<Utc>>) -> mpsc::Receiver<Result<ActivityExport, ApiError>> {
+        use sqlx::Row;
+
+        let (sender, receiver) = mpsc::channel(EXPORT_BUFFER_ROWS);
+        let pool = self.pool.clone();
+        let team = query.team.as_deref().map(Team::normalize_id);
+        let athlete_id = query.athlete_id.clone();
+        let sport_type = query.sport_type.clone();
+
+        tokio::spawn(async move {
+            println!("[DB] stream_activities: Starting export between {:?} and {:?}", start, end);
+            let mut rows = sqlx::query(
+                r#"
+                SELECT b.id, b.strava_activity_id, b.date, b.first_seen_at, b.date_confidence, b.resource_state, b.name, b.distance, b.moving_time,
+                        b.elapsed_time, b.total_elevation_gain, b.sport_type, b.workout_type, b.device_name, b.athlete_name, b.athlete_id,
+                        CASE WHEN a.id IS NULL THEN NULL ELSE COALESCE(m.team, LOWER(a.team)) END AS team
+                FROM bullshark_activities b
+                LEFT JOIN athletes a ON a.id = b.athlete_id
+                LEFT JOIN team_memberships m ON m.athlete_id = b.athlete_id
+                    AND b.date >= m.valid_from
+                    AND (m.valid_to IS NULL OR b.date < m.valid_to)
+                WHERE b.deleted_at IS NULL
+                  AND ($1::timestamptz IS NULL OR b.date >= $1)
+                  AND ($2::timestamptz IS NULL OR b.date <= $2)
+                  AND ($3::text IS NULL OR COALESCE(m.team, LOWER(a.team)) = $3)
+                  AND ($4::text IS NULL OR b.athlete_id = $4)
+                  AND ($5::text IS NULL OR b.sport_type = $5)
+                ORDER BY b.date ASC, b.id ASC
+                "#
+            )
+            .bind(start)
+            .bind(end)
+            .bind(team)
+            .bind(athlete_id)
+            .bind(sport_type)
+            .fetch(&pool);
+
+            let mut count: u64 = 0;
+            while let Some(row) = rows.next().await {
+                let export = row
+                    .map(|row| {
+                        let team: Option<String> = row.get("team");
+                        ActivityExport {
+                            activity: database_utils::map_row_to_activity(row),
+                            team,
+                        }
+                    })
+                    .map_err(|e| ApiError::DatabaseError(format!("Failed to stream activities: {}", e)));
+                let failed = export.is_err();
+                if sender.send(export).await.is_err() || failed {
+                    break;
+                }
+                count += 1;
+            }
+            println!("[DB] stream_activities: Export finished after {} rows", count);
+        });
+
+        receiver
+    }
+    // MARK: Exports End
+
+
+
+
+
+    // MARK: Events
+    pub async fn upsert_event(&self, event: &RaceEvent) -> Result<(), ApiError> {
+        println!("[DB] upsert_event: Upserting event '{}'", event.id);
+        sqlx::query(
+            r#"
+            INSERT INTO events
+            (id, name, date, distance)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (id) DO UPDATE SET
+                name = EXCLUDED.name,
+                date = EXCLUDED.date,
+                distance = EXCLUDED.distance
+            "#
+        )
+        .bind(&event.id)
+        .bind(&event.name)
+        .bind(event.date)
+        .bind(event.distance)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| match e.as_database_error() {
+            // events_lower_id_idx rejects ids differing only by case
+            Some(db_error) if db_error.is_unique_violation() => ApiError::BadRequest(format!("An event with id '{}' already exists in a different case", event.id)),
+            _ => ApiError::DatabaseError(format!("Failed to upsert event: {}", e)),
+        })?;
+
+        Ok(())
+    }
+
+    /// Scheduled races first by date, then generic distances by name
+    pub async fn read_all_events(&self) -> Result<Vec<RaceEvent>, ApiError> {
+        println!("[DB] read_all_events: Starting query for all events");
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, date, distance
+            FROM events
+            ORDER BY date ASC NULLS LAST, name ASC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch events: {}", e)))?;
+
+        let events: Vec<RaceEvent> = rows.into_iter().map(database_utils::map_row_to_event).collect();
+
+        println!("[DB] read_all_events: Query completed, returned {} events", events.len());
+        Ok(events)
+    }
+
+    pub async fn read_event(&self, id: &str) -> Result<Option<RaceEvent>, ApiError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, name, date, distance
+            FROM events
+            WHERE LOWER(id) = LOWER($1)
+            "#
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch event: {}", e)))?;
+
+        Ok(row.map(database_utils::map_row_to_event))
+    }
+
+    pub async fn read_athletes_by_event(&self, event_id: &str) -> Result<Vec<Athlete>, ApiError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, name, team, event, strava_id
+            FROM athletes
+            WHERE event = $1
+            ORDER BY name ASC
+            "#
+        )
+        .bind(event_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch athletes for event: {}", e)))?;
+
+        Ok(rows.into_iter().map(database_utils::map_row_to_athlete).collect())
+    }
+    // MARK: Events End
+
+
+
+
+
+    // MARK: Goals
+    pub async fn insert_goal(&self, goal: &Goal) -> Result<(), ApiError> {
+        println!("[DB] insert_goal: Inserting {} {} goal '{}' for athlete '{}'", goal.period.as_str(), goal.metric.as_str(), goal.id, goal.athlete_id);
+        sqlx::query(
+            r#"
+            INSERT INTO goals
+            (id, athlete_id, metric, period, target, competition_id, sport_types, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#
+        )
+        .bind(&goal.id)
+        .bind(&goal.athlete_id)
+        .bind(goal.metric.as_str())
+        .bind(goal.period.as_str())
+        .bind(goal.target)
+        .bind(&goal.competition_id)
+        .bind(&goal.sport_types)
+        .bind(goal.created_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to insert goal: {}", e)))?;
+
+        Ok(())
+    }
+
+    /// Active goals first, then archived, oldest first within each
+    pub async fn read_goals_for_athlete(&self, athlete_id: &str) -> Result<Vec<Goal>, ApiError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT id, athlete_id, metric, period, target, competition_id, sport_types, created_at, archived_at
+            FROM goals
+            WHERE athlete_id = $1
+            ORDER BY archived_at IS NOT NULL, created_at ASC
+            "#
+        )
+        .bind(athlete_id)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch goals: {}", e)))?;
+
+        Ok(rows.into_iter().map(database_utils::map_row_to_goal).collect())
+    }
+
+    pub async fn read_goal(&self, athlete_id: &str, id: &str) -> Result<Option<Goal>, ApiError> {
+        let row = sqlx::query(
+            r#"
+            SELECT id, athlete_id, metric, period, target, competition_id, sport_types, created_at, archived_at
+            FROM goals
+            WHERE athlete_id = $1 AND id = $2
+            "#
+        )
+        .bind(athlete_id)
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to fetch goal: {}", e)))?;
+
+        Ok(row.map(database_utils::map_row_to_goal))
+    }
+
+    pub async fn count_active_goals(&self, athlete_id: &str) -> Result<i64, ApiError> {
+        use sqlx::Row;
+
+        let row = sqlx::query("SELECT COUNT(*) AS count FROM goals WHERE athlete_id = $1 AND archived_at IS NULL")
+            .bind(athlete_id)
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|e| ApiError::DatabaseError(format!("Failed to count goals: {}", e)))?;
+
+        Ok(row.get("count"))
+    }
+
+    /// Returns false if the goal doesn't exist or is already archived
+    pub async fn archive_goal(&self, athlete_id: &str, id: &str) -> Result<bool, ApiError> {
+        println!("[DB] archive_goal: Archiving goal '{}' for athlete '{}'", id, athlete_id);
+        let result = sqlx::query(
+            r#"
+            UPDATE goals
+            SET archived_at = NOW()
+            WHERE athlete_id = $1 AND id = $2 AND archived_at IS NULL
+            "#
+        )
+        .bind(athlete_id)
+        .bind(id)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::DatabaseError(format!("Failed to archive goal: {}", e)))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+    // MARK: Goals End
+}
+
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use sqlx::{Row, postgres::PgPoolOptions};
+
+    use super::Database;
+    use crate::{models::athlete::Athlete, services::athlete_resolver::AthleteResolver};
+
+    fn get_athlete(id: &str, name: &str) -> Athlete {
+        Athlete {
+            id: id.to_string(),
+            name: name.to_string(),
+            team: "team-a".to_string(),
+            event: "marathon".to_string(),
+            strava_id: None,
+        }
+    }
+
+    // Runs against TEST_DATABASE_URL on a single connection, so the temp table shadows the real one
+    #[tokio::test]
+    async fn set_activity_athlete_ids_keeps_webhook_owner_on_ambiguous_name() {
+        let Ok(database_url) = std::env::var("TEST_DATABASE_URL") else {
+            eprintln!("[TEST] TEST_DATABASE_URL is not set, skipping");
+            return;
+        };
+        let pool = PgPoolOptions::new().max_connections(1).connect(&database_url).await.unwrap();
+        sqlx::query(
+            r#"
+            CREATE TEMP TABLE bullshark_activities (
+                id TEXT PRIMARY KEY,
+                athlete_name TEXT,
+                athlete_id TEXT,
+                strava_activity_id BIGINT
+            )
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO bullshark_activities (id, athlete_name, athlete_id, strava_activity_id)
+            VALUES ('club', 'Alex S.', 'alex-smith', NULL), ('webhook', 'Alex S.', 'alex-smith', 42)
+            "#
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        // A second "Alex S." joins, so the club feed name no longer resolves
+        let resolver = AthleteResolver::new(vec![get_athlete("alex-smith", "Alex Smith"), get_athlete("alex-stone", "Alex Stone")], vec![]);
+        let athlete_ids = vec![resolver.resolve("Alex S.").map(|athlete| athlete.id.clone())];
+        assert_eq!(athlete_ids, vec![None]);
+
+        let db = Database::new(pool.clone());
+        let updated = db.set_activity_athlete_ids(&["Alex S.".to_string()], &athlete_ids).await.unwrap();
+        assert_eq!(updated, 1);
+
+        let rows = sqlx::query("SELECT id, athlete_id FROM bullshark_activities ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        let athlete_ids: Vec<(String, Option<String>)> = rows.iter().map(|row| (row.get("id"), row.get("athlete_id"))).collect();
+        assert_eq!(athlete_ids, vec![
+            ("club".to_string(), None),
+            ("webhook".to_string(), Some("alex-smith".to_string())),
+        ]);
+    }
+}
@@ -0,0 +1,111 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{error::ApiError, models::bullshark::{BullSharkActivity, DateConfidence}, services::{athlete_resolver::AthleteResolver, database::Database, strava_client::StravaClient}, utils::database_utils};
+
+// How far before the previous sync we look in an athlete's own feed for a matching start date
+const EXACT_LOOKBACK_DAYS: i64 = 3;
+
+/*
+The club activities endpoint has no start dates, so every activity arrives
+stamped with the time we first saw it. The resolver improves on that in two passes:
+1. Estimate: new activities appear newest-first between the previous sync run and now,
+   so we spread them across that window by position and subtract their elapsed time.
+2. Exact: for athletes who have authorized us, read their own feed and take the real start date.
+*/
+pub struct DateResolver {
+    db: Arc<Database>,
+}
+
+impl DateResolver {
+    pub fn new(db: Arc<Database>) -> Self {
+        DateResolver { db }
+    }
+
+    pub async fn resolve_dates(&self, strava_client: &StravaClient, resolver: &AthleteResolver, activities: &mut [BullSharkActivity], first_seen_at: DateTime<Utc>) -> Result<(), ApiError> {
+        if activities.is_empty() {
+            return Ok(());
+        }
+
+        let previous_sync = self.db.get_last_sync_at().await?;
+        if let Some(previous_sync) = previous_sync {
+            self.estimate_from_sync_window(activities, previous_sync, first_seen_at);
+        } else {
+            println!("[DATE_RESOLVER] resolve_dates: no previous sync found, keeping first seen dates");
+        }
+
+        let lookback_start = previous_sync.unwrap_or(first_seen_at) - Duration::days(EXACT_LOOKBACK_DAYS);
+        self.resolve_exact_dates(strava_client, resolver, activities, lookback_start, first_seen_at).await?;
+        Ok(())
+    }
+
+    /// `activities` must be in club feed order (newest first)
+    fn estimate_from_sync_window(&self, activities: &mut [BullSharkActivity], previous_sync: DateTime<Utc>, now: DateTime<Utc>) {
+        let window = now - previous_sync;
+        if window <= Duration::zero() {
+            return;
+        }
+
+        let count = activities.len() as i32;
+        for (index, activity) in activities.iter_mut().enumerate() {
+            // Oldest activity gets the slot closest to the previous sync
+            let slot = count - index as i32;
+            let uploaded_at = previous_sync + window * slot / (count + 1);
+            let started_at = uploaded_at - Duration::seconds(activity.elapsed_time.unwrap_or(0));
+
+            activity.date = database_utils::to_pacific(started_at);
+            activity.date_confidence = DateConfidence::Estimated;
+        }
+        println!("[DATE_RESOLVER] estimate_from_sync_window: estimated {} dates between {} and {}", count, previous_sync, now);
+    }
+
+    async fn resolve_exact_dates(&self, strava_client: &StravaClient, resolver: &AthleteResolver, activities: &mut [BullSharkActivity], after: DateTime<Utc>, before: DateTime<Utc>) -> Result<(), ApiError> {
+        // Group activity indexes by the Strava id of the roster athlete they resolve to
+        let mut athlete_activities: HashMap<String, (String, Vec<usize>)> = HashMap::new();
+        for (index, activity) in activities.iter().enumerate() {
+            if let Some(athlete) = activity.athlete_name.as_deref().and_then(|name| resolver.resolve(name))
+                && let Some(strava_id) = &athlete.strava_id {
+                athlete_activities
+                    .entry(strava_id.clone())
+                    .or_insert_with(|| (athlete.name.clone(), Vec::new()))
+                    .1
+                    .push(index);
+            }
+        }
+
+        let strava_ids: Vec<String> = athlete_activities.keys().cloned().collect();
+        let authorized_ids = self.db.get_auth_token_ids(&strava_ids).await?;
+
+        for (user_id, (athlete_name, indexes)) in athlete_activities {
+            if !authorized_ids.contains(&user_id) {
+                continue;
+            }
+
+            let own_activities = match strava_client.read_athlete_activities(&user_id, after.timestamp(), before.timestamp()).await {
+                Ok(own_activities) => own_activities,
+                Err(e) => {
+                    // An athlete revoking access shouldn't fail the whole sync
+                    eprintln!("[DATE_RESOLVER] resolve_exact_dates: could not read activities for {}: {:?}", athlete_name, e);
+                    continue;
+                }
+            };
+
+            for index in indexes {
+                let activity = &mut activities[index];
+                let matched = own_activities.iter().find(|own| {
+                    own.moving_time == activity.moving_time
+                        && own.elapsed_time == activity.elapsed_time
+                        && matches!((own.distance, activity.distance), (Some(a), Some(b)) if (a - b).abs() < 1.0)
+                });
+
+                if let Some(own) = matched {
+                    activity.date = database_utils::to_pacific(own.start_date);
+                    activity.date_confidence = DateConfidence::Exact;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
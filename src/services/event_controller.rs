@@ -0,0 +1,149 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+use chrono_tz::America::Los_Angeles;
+
+use crate::{error::ApiError, models::{event::{CohortAthlete, EventDashboard, EventDashboardQuery, EventWeek, TaperIndicator, TrainingPhase}, streak}, services::database::Database};
+
+const DEFAULT_DASHBOARD_WEEKS: u32 = 16;
+const MAX_DASHBOARD_WEEKS: u32 = 52;
+// Race week is excluded, so a three week taper covers the 21 days before it
+const TAPER_WEEKS: i64 = 3;
+// Cutting at least a fifth of peak volume counts as tapering
+const TAPER_VOLUME_DROP: f64 = 0.2;
+
+pub struct EventController {
+    db: Arc<Database>,
+}
+
+impl EventController {
+    pub fn new(db: Arc<Database>) -> Self {
+        EventController { db }
+    }
+
+    pub async fn get_dashboard(&self, event_id: &str, query: EventDashboardQuery) -> Result<EventDashboard, ApiError> {
+        let event = self.db.read_event(event_id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No event found with id: {}", event_id)))?;
+        let athletes = self.db.read_athletes_by_event(&event.id).await?;
+
+        let now = Utc::now();
+        let today = now.with_timezone(&Los_Angeles).date_naive();
+        let days_until_race = event.date.map(|date| (date - today).num_days());
+
+        // Show training up to race week, or up to this week while the race is still ahead
+        let last_day = event.date.map_or(today, |date| date.min(today));
+        let week_count = query.weeks.unwrap_or(DEFAULT_DASHBOARD_WEEKS).clamp(1, MAX_DASHBOARD_WEEKS) as i64;
+        let last_week = streak::get_week_start(last_day);
+        let week_starts: Vec<NaiveDate> = (0..week_count)
+            .rev()
+            .map(|offset| last_week - Duration::weeks(offset))
+            .collect();
+
+        let start = Self::get_pacific_midnight(week_starts[0])?;
+        let end = Self::get_pacific_midnight(last_week + Duration::weeks(1))?.min(now);
+        let athlete_ids: Vec<String> = athletes.iter().map(|athlete| athlete.id.clone()).collect();
+        let activities = self.db.get_activities_for_athletes(&athlete_ids, start, end).await?;
+        println!("[EVENT_CONTROLLER] get_dashboard: '{}' has {} athletes and {} activities over {} weeks", event.id, athletes.len(), activities.len(), week_count);
+
+        let week_indexes: HashMap<NaiveDate, usize> = week_starts.iter().enumerate().map(|(index, week)| (*week, index)).collect();
+        let mut cohort: Vec<CohortAthlete> = athletes
+            .into_iter()
+            .map(|athlete| CohortAthlete {
+                athlete_id: athlete.id,
+                athlete_name: athlete.name,
+                team: athlete.team,
+                weekly_km: vec![0.0; week_starts.len()],
+                weekly_longest_run_km: vec![0.0; week_starts.len()],
+                longest_run_km: 0.0,
+            })
+            .collect();
+        let cohort_indexes: HashMap<String, usize> = cohort.iter().enumerate().map(|(index, athlete)| (athlete.athlete_id.clone(), index)).collect();
+
+        for activity in activities.iter().filter(|activity| activity.is_run()) {
+            // Activity dates are already in Pacific time
+            let week = streak::get_week_start(activity.date.date_naive());
+            let (Some(week_index), Some(athlete_index)) = (
+                week_indexes.get(&week),
+                activity.athlete_id.as_ref().and_then(|id| cohort_indexes.get(id)),
+            ) else {
+                continue;
+            };
+
+            let kilometers = activity.distance.unwrap_or(0.0) / 1000.0;
+            let athlete = &mut cohort[*athlete_index];
+            athlete.weekly_km[*week_index] += kilometers;
+            athlete.weekly_longest_run_km[*week_index] = athlete.weekly_longest_run_km[*week_index].max(kilometers);
+            athlete.longest_run_km = athlete.longest_run_km.max(kilometers);
+        }
+
+        let weeks = Self::build_weeks(&week_starts, &cohort);
+        // The current week is still filling up, so it can't show a taper yet
+        let complete_weeks = match week_starts.last() {
+            Some(week) if *week == streak::get_week_start(today) => &weeks[..weeks.len() - 1],
+            _ => &weeks[..],
+        };
+        let taper = Self::build_taper(days_until_race, complete_weeks);
+
+        Ok(EventDashboard {
+            event,
+            days_until_race,
+            weeks,
+            cohort,
+            taper,
+            generated_at: now,
+        })
+    }
+
+    fn build_weeks(week_starts: &[NaiveDate], cohort: &[CohortAthlete]) -> Vec<EventWeek> {
+        week_starts
+            .iter()
+            .enumerate()
+            .map(|(index, week_start)| {
+                let active: Vec<&CohortAthlete> = cohort.iter().filter(|athlete| athlete.weekly_km[index] > 0.0).collect();
+                let total_km: f64 = active.iter().map(|athlete| athlete.weekly_km[index]).sum();
+                let longest_runs = active.iter().map(|athlete| athlete.weekly_longest_run_km[index]);
+
+                EventWeek {
+                    week_start: *week_start,
+                    active_athletes: active.len(),
+                    total_km,
+                    average_km: if cohort.is_empty() { 0.0 } else { total_km / cohort.len() as f64 },
+                    longest_run_km: longest_runs.clone().fold(0.0, f64::max),
+                    average_longest_run_km: if active.is_empty() { 0.0 } else { longest_runs.sum::<f64>() / active.len() as f64 },
+                }
+            })
+            .collect()
+    }
+
+    fn build_taper(days_until_race: Option<i64>, complete_weeks: &[EventWeek]) -> TaperIndicator {
+        let phase = match days_until_race {
+            None => TrainingPhase::Unscheduled,
+            Some(days) if days < 0 => TrainingPhase::PostRace,
+            Some(days) if days < 7 => TrainingPhase::RaceWeek,
+            Some(days) if days < 7 * (TAPER_WEEKS + 1) => TrainingPhase::Taper,
+            Some(_) => TrainingPhase::Build,
+        };
+
+        let peak = complete_weeks.iter().max_by(|a, b| a.average_km.total_cmp(&b.average_km));
+        let peak_week_km = peak.map_or(0.0, |week| week.average_km);
+        let last_week_km = complete_weeks.last().map_or(0.0, |week| week.average_km);
+        let volume_drop = (peak_week_km > 0.0).then(|| 1.0 - last_week_km / peak_week_km);
+
+        TaperIndicator {
+            phase,
+            peak_week_km,
+            peak_week_start: peak.filter(|_| peak_week_km > 0.0).map(|week| week.week_start),
+            last_week_km,
+            volume_drop,
+            tapering: volume_drop.is_some_and(|drop| drop >= TAPER_VOLUME_DROP),
+        }
+    }
+
+    fn get_pacific_midnight(day: NaiveDate) -> Result<DateTime<Utc>, ApiError> {
+        Los_Angeles
+            .from_local_datetime(&day.and_hms_opt(0, 0, 0).unwrap())
+            .earliest()
+            .map(|date| date.with_timezone(&Utc))
+            .ok_or_else(|| ApiError::InternalConversionError(format!("Invalid datetime conversion for {}", day)))
+    }
+}
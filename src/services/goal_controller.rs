@@ -0,0 +1,208 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::{error::ApiError, models::{bullshark::BullSharkActivity, goal::{CreateGoal, Goal, GoalPeriod, GoalPeriodResult, GoalProgress, GoalProjection, GoalStatus}}, services::database::Database, utils::time_utils};
+
+// Goals are created without an athlete login, so cap how many one athlete can have running
+const MAX_ACTIVE_GOALS: i64 = 10;
+const MAX_GOAL_HISTORY: usize = 104;
+
+pub struct GoalController {
+    db: Arc<Database>,
+}
+
+impl GoalController {
+    pub fn new(db: Arc<Database>) -> Self {
+        GoalController { db }
+    }
+
+    pub async fn create_goal(&self, athlete_id: &str, input: CreateGoal) -> Result<Goal, ApiError> {
+        self.db.read_athlete(athlete_id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No athlete found with id: {}", athlete_id)))?;
+
+        let competition = match &input.competition_id {
+            Some(id) => Some(self.db.read_competition(id).await?
+                .ok_or_else(|| ApiError::NotFound(format!("No competition found with id: {}", id)))?),
+            None => None,
+        };
+        if let Some(competition) = &competition
+            && competition.end_date.is_some_and(|end_date| end_date < Utc::now()) {
+            return Err(ApiError::BadRequest(format!("Competition '{}' has already ended", competition.id)));
+        }
+
+        if self.db.count_active_goals(athlete_id).await? >= MAX_ACTIVE_GOALS {
+            return Err(ApiError::BadRequest(format!("Athletes can have at most {} active goals", MAX_ACTIVE_GOALS)));
+        }
+
+        let goal = input.into_goal(uuid::Uuid::new_v4().to_string(), athlete_id, competition.as_ref())?;
+        self.db.insert_goal(&goal).await?;
+        Ok(goal)
+    }
+
+    pub async fn get_goals(&self, athlete_id: &str) -> Result<Vec<GoalProgress>, ApiError> {
+        self.db.read_athlete(athlete_id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No athlete found with id: {}", athlete_id)))?;
+
+        let goals = self.db.read_goals_for_athlete(athlete_id).await?;
+        let activities = self.get_activities_oldest_first(athlete_id).await?;
+        println!("[GOAL_CONTROLLER] get_goals: Tracking {} goals for '{}' over {} activities", goals.len(), athlete_id, activities.len());
+
+        let now = Utc::now();
+        let mut progress: Vec<GoalProgress> = Vec::with_capacity(goals.len());
+        for goal in goals {
+            progress.push(self.build_progress(goal, &activities, now).await?);
+        }
+        Ok(progress)
+    }
+
+    pub async fn get_goal(&self, athlete_id: &str, goal_id: &str) -> Result<GoalProgress, ApiError> {
+        let goal = self.db.read_goal(athlete_id, goal_id).await?
+            .ok_or_else(|| ApiError::NotFound(format!("No goal found with id: {}", goal_id)))?;
+        let activities = self.get_activities_oldest_first(athlete_id).await?;
+        self.build_progress(goal, &activities, Utc::now()).await
+    }
+
+    pub async fn archive_goal(&self, athlete_id: &str, goal_id: &str) -> Result<(), ApiError> {
+        if !self.db.archive_goal(athlete_id, goal_id).await? {
+            return Err(ApiError::NotFound(format!("No active goal found with id: {}", goal_id)));
+        }
+        Ok(())
+    }
+
+    async fn get_activities_oldest_first(&self, athlete_id: &str) -> Result<Vec<BullSharkActivity>, ApiError> {
+        let mut activities = self.db.get_activities_for_athlete(athlete_id).await?;
+        activities.reverse();
+        Ok(activities)
+    }
+
+    async fn build_progress(&self, goal: Goal, activities: &[BullSharkActivity], now: DateTime<Utc>) -> Result<GoalProgress, ApiError> {
+        // Periods cut short by archiving the goal aren't judged
+        let cutoff = goal.archived_at.unwrap_or(now).min(now);
+
+        let mut current: Option<GoalPeriodResult> = None;
+        let mut history: Vec<GoalPeriodResult> = Vec::new();
+        for (start, end) in self.get_periods(&goal, cutoff).await? {
+            let finished = end.is_some_and(|end| end < cutoff);
+            let in_progress = goal.is_active() && !finished;
+            if !finished && !in_progress {
+                continue;
+            }
+
+            let (value, completed_at) = Self::accumulate(&goal, activities, start, end.unwrap_or(now).min(now));
+            let status = if value >= goal.target {
+                GoalStatus::Hit
+            } else if in_progress {
+                GoalStatus::InProgress
+            } else {
+                GoalStatus::Missed
+            };
+            let result = GoalPeriodResult {
+                start,
+                end,
+                value,
+                target: goal.target,
+                fraction_complete: value / goal.target,
+                status,
+                completed_at,
+            };
+
+            if in_progress {
+                current = Some(result);
+            } else {
+                history.push(result);
+            }
+        }
+
+        history.reverse();
+        history.truncate(MAX_GOAL_HISTORY);
+        let hit_count = history.iter().filter(|result| result.status == GoalStatus::Hit).count();
+        let missed_count = history.len() - hit_count;
+        let projection = current.as_ref().map(|current| Self::project(current, now));
+
+        Ok(GoalProgress {
+            goal,
+            current,
+            projection,
+            history,
+            hit_count,
+            missed_count,
+        })
+    }
+
+    /// Every period from the one the goal was created in up to the one containing `last`, oldest first
+    async fn get_periods(&self, goal: &Goal, last: DateTime<Utc>) -> Result<Vec<(DateTime<Utc>, Option<DateTime<Utc>>)>, ApiError> {
+        if goal.period == GoalPeriod::Competition {
+            let Some(competition_id) = &goal.competition_id else {
+                return Ok(Vec::new());
+            };
+            let competition = self.db.read_competition(competition_id).await?
+                .ok_or_else(|| ApiError::NotFound(format!("No competition found with id: {}", competition_id)))?;
+            if competition.start_date > last {
+                return Ok(Vec::new());
+            }
+            return Ok(vec![(competition.start_date, competition.end_date)]);
+        }
+
+        let get_window = |reference: DateTime<Utc>| match goal.period {
+            GoalPeriod::Month => time_utils::get_month_window(reference),
+            _ => time_utils::get_week_window(reference),
+        };
+
+        let mut periods: Vec<(DateTime<Utc>, Option<DateTime<Utc>>)> = Vec::new();
+        let (mut start, mut end) = get_window(goal.created_at)?;
+        while start <= last {
+            periods.push((start, Some(end)));
+            (start, end) = get_window(end + Duration::seconds(1))?;
+        }
+
+        // Keep the history window plus the current period
+        let skip = periods.len().saturating_sub(MAX_GOAL_HISTORY + 1);
+        Ok(periods.split_off(skip))
+    }
+
+    /// The goal's total between `start` and `end`, and when the activity that reached the target started
+    fn accumulate(goal: &Goal, activities: &[BullSharkActivity], start: DateTime<Utc>, end: DateTime<Utc>) -> (f64, Option<DateTime<Utc>>) {
+        let first = activities.partition_point(|activity| activity.date.with_timezone(&Utc) < start);
+        let last = activities.partition_point(|activity| activity.date.with_timezone(&Utc) <= end);
+
+        let mut value = 0.0;
+        let mut completed_at: Option<DateTime<Utc>> = None;
+        for activity in activities[first..last.max(first)].iter().filter(|activity| goal.counts(activity)) {
+            value += goal.metric.get_value(activity);
+            if completed_at.is_none() && value >= goal.target {
+                completed_at = Some(activity.date.with_timezone(&Utc));
+            }
+        }
+        (value, completed_at)
+    }
+
+    /// Extends the rate of accumulation since the start of the period
+    fn project(current: &GoalPeriodResult, now: DateTime<Utc>) -> GoalProjection {
+        let hit = current.value >= current.target;
+        let remaining = (current.target - current.value).max(0.0);
+        let elapsed_seconds = (now - current.start).num_seconds().max(1) as f64;
+        let rate = current.value / elapsed_seconds;
+
+        let elapsed_fraction = current.end
+            .map(|end| (elapsed_seconds / (end - current.start).num_seconds().max(1) as f64).min(1.0));
+        let projected_value = elapsed_fraction.map(|fraction| current.value / fraction);
+        let projected_completion = if hit || rate <= 0.0 {
+            None
+        } else {
+            Duration::try_seconds((remaining / rate).ceil() as i64).map(|duration| now + duration)
+        };
+        let on_track = hit || match current.end {
+            Some(end) => projected_completion.is_some_and(|completion| completion <= end),
+            None => projected_completion.is_some(),
+        };
+
+        GoalProjection {
+            remaining,
+            elapsed_fraction,
+            projected_value,
+            projected_completion,
+            on_track,
+        }
+    }
+}
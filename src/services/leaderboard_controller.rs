@@ -0,0 +1,129 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{DateTime, Utc};
+
+use crate::{error::ApiError, models::{leaderboard::{AthleteTotals, Leaderboard, LeaderboardEntry, LeaderboardMetric, LeaderboardPeriod, LeaderboardQuery}, team::Team}, services::database::Database, utils::time_utils};
+
+pub struct LeaderboardController {
+    db: Arc<Database>,
+}
+
+impl LeaderboardController {
+    pub fn new(db: Arc<Database>) -> Self {
+        LeaderboardController { db }
+    }
+
+    pub async fn get_leaderboard(&self, query: LeaderboardQuery) -> Result<Leaderboard, ApiError> {
+        let (start, end, competition_sport_types) = self.get_period_window(&query).await?;
+        let (previous_start, previous_end) = match query.period {
+            LeaderboardPeriod::Week => time_utils::get_week_window(start - chrono::Duration::days(7))?,
+            LeaderboardPeriod::Month => time_utils::get_month_window(start - chrono::Duration::seconds(1))?,
+            LeaderboardPeriod::Competition | LeaderboardPeriod::Custom => time_utils::get_previous_window(start, end),
+        };
+
+        let team = query.team.as_deref().map(Team::normalize_id);
+        // An explicit sport type wins over the competition's sport types
+        let sport_types = match &query.sport_type {
+            Some(sport_type) => Some(vec![sport_type.clone()]),
+            None => competition_sport_types,
+        };
+
+        println!("[LEADERBOARD] get_leaderboard: {:?} over {:?} from {} to {}", query.metric, query.period, start, end);
+        let current = self.db.get_athlete_totals(start, end, team.as_deref(), sport_types.as_deref(), None).await?;
+        let previous = self.db.get_athlete_totals(previous_start, previous_end, team.as_deref(), sport_types.as_deref(), None).await?;
+
+        let previous_ranks: HashMap<String, (usize, f64)> = Self::rank(previous, query.metric)
+            .into_iter()
+            .map(|(rank, value, totals)| (totals.athlete_id, (rank, value)))
+            .collect();
+
+        let single_sport = sport_types.as_ref().is_some_and(|sport_types| sport_types.len() == 1);
+        let entries = Self::rank(current, query.metric)
+            .into_iter()
+            .map(|(rank, value, totals)| {
+                let previous = previous_ranks.get(&totals.athlete_id);
+                LeaderboardEntry {
+                    rank,
+                    value,
+                    activity_count: totals.activity_count,
+                    metrics: single_sport.then(|| totals.get_metrics()),
+                    previous_rank: previous.map(|(rank, _)| *rank),
+                    previous_value: previous.map(|(_, value)| *value),
+                    delta: value - previous.map(|(_, value)| *value).unwrap_or(0.0),
+                    rank_change: previous.map(|(previous_rank, _)| *previous_rank as i64 - rank as i64),
+                    athlete_id: totals.athlete_id,
+                    athlete_name: totals.athlete_name,
+                    team: totals.team,
+                }
+            })
+            .collect();
+
+        Ok(Leaderboard {
+            metric: query.metric,
+            period: query.period,
+            start,
+            end,
+            previous_start,
+            previous_end,
+            team,
+            sport_types,
+            entries,
+        })
+    }
+
+    /// The window for the requested period, plus the competition's sport types for the competition period
+    async fn get_period_window(&self, query: &LeaderboardQuery) -> Result<(DateTime<Utc>, DateTime<Utc>, Option<Vec<String>>), ApiError> {
+        match query.period {
+            LeaderboardPeriod::Week => {
+                let (start, end) = time_utils::get_week_window(Utc::now())?;
+                Ok((start, end, None))
+            }
+            LeaderboardPeriod::Month => {
+                let (start, end) = time_utils::get_month_window(Utc::now())?;
+                Ok((start, end, None))
+            }
+            LeaderboardPeriod::Competition => {
+                let competition = match &query.competition_id {
+                    Some(id) => self.db.read_competition(id).await?
+                        .ok_or_else(|| ApiError::NotFound(format!("No competition found with id: {}", id)))?,
+                    None => self.db.read_current_competition().await?
+                        .ok_or_else(|| ApiError::NotFound("No competition has started yet".to_string()))?,
+                };
+                let (start, end) = competition.get_window();
+                Ok((start, end, Some(competition.sport_types)))
+            }
+            LeaderboardPeriod::Custom => {
+                let (Some(start), Some(end)) = (&query.start, &query.end) else {
+                    return Err(ApiError::BadRequest("The custom period needs start and end".to_string()));
+                };
+                let start = time_utils::parse_datetime("start", start)?;
+                let end = time_utils::parse_datetime("end", end)?;
+                if end <= start {
+                    return Err(ApiError::BadRequest("end must be after start".to_string()));
+                }
+                Ok((start, end, None))
+            }
+        }
+    }
+
+    /// Orders athletes by the metric, highest first, with ties sharing a rank
+    fn rank(totals: Vec<AthleteTotals>, metric: LeaderboardMetric) -> Vec<(usize, f64, AthleteTotals)> {
+        let mut valued: Vec<(f64, AthleteTotals)> = totals
+            .into_iter()
+            .map(|totals| (totals.get_value(metric), totals))
+            .collect();
+        valued.sort_by(|(a_value, a), (b_value, b)| {
+            b_value.total_cmp(a_value).then_with(|| a.athlete_name.cmp(&b.athlete_name))
+        });
+
+        let mut ranked: Vec<(usize, f64, AthleteTotals)> = Vec::with_capacity(valued.len());
+        for (index, (value, totals)) in valued.into_iter().enumerate() {
+            let rank = match ranked.last() {
+                Some((previous_rank, previous_value, _)) if *previous_value == value => *previous_rank,
+                _ => index + 1,
+            };
+            ranked.push((rank, value, totals));
+        }
+        ranked
+    }
+}
@@ -2,3 +2,12 @@ pub mod strava_client;
 pub mod activity_controller;
 pub mod database;
 pub mod auth_controller;
+pub mod strava_http;
+pub mod date_resolver;
+pub mod webhook_controller;
+pub mod athlete_resolver;
+pub mod leaderboard_controller;
+pub mod athlete_controller;
+pub mod workout_controller;
+pub mod goal_controller;
+pub mod event_controller;
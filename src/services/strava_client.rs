@@ -1,37 +1,35 @@
-use crate::models::club::ClubActivity;
+use crate::models::{club::ClubActivity, rate_limit::RateLimitStatus, strava_activity::SummaryActivity};
 use crate::error::{
     ApiError,
 };
 use crate::services::auth_controller::{AuthController};
+use std::sync::Arc;
 
 pub struct StravaClient {
-    auth_controller: AuthController, 
+    auth_controller: Arc<AuthController>, 
 }
 
 impl StravaClient {
-    pub fn new(auth_controller: AuthController) -> Self {
+    pub fn new(auth_controller: Arc<AuthController>) -> Self {
         StravaClient { auth_controller }
     }
 
-    pub async fn read_last_100_activities(&self) -> Result<Vec<ClubActivity>, ApiError> {
+    pub fn get_sync_max_pages(&self) -> u32 {
+        self.auth_controller.get_sync_max_pages()
+    }
+
+    pub async fn read_club_activities_page(&self, page: u32, per_page: u32) -> Result<Vec<ClubActivity>, ApiError> {
         let fresh_token = self.auth_controller.get_valid_auth_token().await?;
         let club_id = self.auth_controller.get_club_id();
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(15))
-            .build()
-            .map_err(|e| ApiError::ExternalAPIError(format!("Failed to build HTTP client: {}", e)))?;
-        let response = client
+        let http = self.auth_controller.get_http();
+        let request = http.client()
         .get(format!("https://www.strava.com/api/v3/clubs/{}/activities", club_id))
         .query(&[
-            ("page", "1"),
-            ("per_page", "100"),
+            ("page", page.to_string().as_str()),
+            ("per_page", per_page.to_string().as_str()),
             ("access_token", &fresh_token),
-        ])
-        .send()
-        .await
-        .map_err(|e| {
-            ApiError::ExternalAPIError(e.to_string())
-        })?;
+        ]);
+        let response = http.send(request).await?;
 
         // Check the status of the response, log details if needed
         if !response.status().is_success() {
@@ -53,6 +51,66 @@ impl StravaClient {
         Ok(club_activities)
     }
 
+    /// Read the activities of an athlete who has authorized us, between two unix timestamps
+    pub async fn read_athlete_activities(&self, user_id: &str, after: i64, before: i64) -> Result<Vec<SummaryActivity>, ApiError> {
+        let fresh_token = self.auth_controller.get_valid_auth_token_for_user(user_id).await?;
+        let http = self.auth_controller.get_http();
+        let request = http.client()
+            .get("https://www.strava.com/api/v3/athlete/activities")
+            .bearer_auth(&fresh_token)
+            .query(&[
+                ("after", after.to_string()),
+                ("before", before.to_string()),
+                ("per_page", "100".to_string()),
+            ]);
+        let response = http.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+
+            eprintln!("Strava API returned error {} for athlete {}: {}", status, user_id, error_text);
+            return Err(ApiError::ExternalAPIError(error_text));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| {
+                eprintln!("Error deserializing athlete activities: {}", e);
+                ApiError::ExternalAPIError(e.to_string())
+            })
+    }
+
+    pub async fn read_activity(&self, user_id: &str, activity_id: i64) -> Result<SummaryActivity, ApiError> {
+        let fresh_token = self.auth_controller.get_valid_auth_token_for_user(user_id).await?;
+        let http = self.auth_controller.get_http();
+        let request = http.client()
+            .get(format!("https://www.strava.com/api/v3/activities/{}", activity_id))
+            .bearer_auth(&fresh_token);
+        let response = http.send(request).await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+
+            eprintln!("Strava API returned error {} for activity {}: {}", status, activity_id, error_text);
+            return Err(ApiError::ExternalAPIError(error_text));
+        }
+
+        response
+            .json()
+            .await
+            .map_err(|e| {
+                eprintln!("Error deserializing activity {}: {}", activity_id, e);
+                ApiError::ExternalAPIError(e.to_string())
+            })
+    }
+
+    pub fn get_rate_limit_status(&self) -> Option<RateLimitStatus> {
+        self.auth_controller.get_http().get_rate_limit_status()
+    }
+
     pub async fn health_check(&self) -> Result<(), ApiError> {
         // Verify we can get a valid auth token from Strava
         self.auth_controller.get_valid_auth_token().await?;
@@ -0,0 +1,134 @@
+use std::{sync::Mutex, time::Duration};
+
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+use crate::{error::ApiError, models::rate_limit::RateLimitStatus};
+
+const MAX_RETRIES: u32 = 3;
+const BASE_BACKOFF_MS: u64 = 1000;
+const MAX_BACKOFF_MS: u64 = 30_000;
+
+/*
+Shared HTTP layer for every call we make to Strava.
+It records the rate limit headers from each response and retries
+5xx responses with exponential backoff plus jitter.
+Strava's limits reset on 15 minute windows, so a 429 fails fast unless
+Retry-After asks for a wait short enough to be worth holding the request.
+*/
+pub struct StravaHttp {
+    client: reqwest::Client,
+    rate_limit: Mutex<Option<RateLimitStatus>>,
+}
+
+impl StravaHttp {
+    pub fn new() -> Result<Self, ApiError> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(15))
+            .build()
+            .map_err(|e| ApiError::ExternalAPIError(format!("Failed to build HTTP client: {}", e)))?;
+
+        Ok(StravaHttp {
+            client,
+            rate_limit: Mutex::new(None),
+        })
+    }
+
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub fn get_rate_limit_status(&self) -> Option<RateLimitStatus> {
+        self.rate_limit.lock().ok().and_then(|status| status.clone())
+    }
+
+    pub async fn send(&self, request: RequestBuilder) -> Result<Response, ApiError> {
+        let mut attempt: u32 = 0;
+        loop {
+            // Checked before every attempt, since the last response may have used up the quota
+            self.check_rate_limit()?;
+
+            let attempt_request = request
+                .try_clone()
+                .ok_or_else(|| ApiError::InternalConversionError("Strava request body could not be cloned for retry".to_string()))?;
+
+            let response = attempt_request
+                .send()
+                .await
+                .map_err(|e| ApiError::ExternalAPIError(format!("Strava API request failed: {}", e)))?;
+
+            self.record_rate_limit(&response);
+
+            let status = response.status();
+            if status == StatusCode::TOO_MANY_REQUESTS {
+                let retry_after = Self::get_retry_after(&response)
+                    .filter(|retry_after| *retry_after <= Duration::from_millis(MAX_BACKOFF_MS));
+                let Some(retry_after) = retry_after.filter(|_| attempt < MAX_RETRIES) else {
+                    let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                    eprintln!("[STRAVA_HTTP] send: rate limited after {} retries, not retrying: {}", attempt, error_text);
+                    return Err(ApiError::RateLimited(format!("Strava rate limit hit: {}", error_text)));
+                };
+
+                println!("[STRAVA_HTTP] send: received 429, retrying after {}s as asked (attempt {}/{})", retry_after.as_secs(), attempt + 1, MAX_RETRIES);
+                tokio::time::sleep(retry_after).await;
+                attempt += 1;
+                continue;
+            }
+            if !status.is_server_error() {
+                return Ok(response);
+            }
+
+            if attempt >= MAX_RETRIES {
+                let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+                eprintln!("[STRAVA_HTTP] send: giving up after {} retries, status {}: {}", attempt, status, error_text);
+                return Err(ApiError::ExternalAPIError(format!("Strava returned {}: {}", status, error_text)));
+            }
+
+            let backoff = self.backoff_for_attempt(attempt);
+            println!("[STRAVA_HTTP] send: received {}, retrying in {}ms (attempt {}/{})", status, backoff.as_millis(), attempt + 1, MAX_RETRIES);
+            tokio::time::sleep(backoff).await;
+            attempt += 1;
+        }
+    }
+
+    fn check_rate_limit(&self) -> Result<(), ApiError> {
+        if let Some(status) = self.get_rate_limit_status()
+            && status.is_exhausted() {
+            eprintln!("[STRAVA_HTTP] check_rate_limit: rate limit exhausted ({}/{} short term, {}/{} daily), skipping request",
+                status.short_term_usage, status.short_term_limit, status.daily_usage, status.daily_limit);
+            return Err(ApiError::RateLimited("Strava rate limit exhausted".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Retry-After in seconds; the HTTP date form isn't used by Strava
+    fn get_retry_after(response: &Response) -> Option<Duration> {
+        response.headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|h| h.to_str().ok())
+            .and_then(|value| value.trim().parse::<u64>().ok())
+            .map(Duration::from_secs)
+    }
+
+    fn record_rate_limit(&self, response: &Response) {
+        let headers = response.headers();
+        let limit = headers.get("X-RateLimit-Limit").and_then(|h| h.to_str().ok());
+        let usage = headers.get("X-RateLimit-Usage").and_then(|h| h.to_str().ok());
+
+        if let (Some(limit), Some(usage)) = (limit, usage)
+            && let Some(status) = RateLimitStatus::from_headers(limit, usage)
+            && let Ok(mut current) = self.rate_limit.lock() {
+            *current = Some(status);
+        }
+    }
+
+    // Equal jitter: a random delay between half and all of the exponential backoff
+    fn backoff_for_attempt(&self, attempt: u32) -> Duration {
+        let exponential = (BASE_BACKOFF_MS * 2u64.pow(attempt)).min(MAX_BACKOFF_MS);
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as u64)
+            .unwrap_or(0);
+        let jitter = nanos % (exponential / 2 + 1);
+        Duration::from_millis(exponential / 2 + jitter)
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use tokio::sync::mpsc;
+
+use crate::{error::ApiError, models::webhook::{SubscriptionChallenge, WebhookEvent}, services::activity_controller::ActivityController};
+
+// Strava expects a response within 2 seconds, so events are queued and handled by a worker
+const WEBHOOK_QUEUE_SIZE: usize = 1000;
+
+#[derive(Clone)]
+pub struct WebhookConfig {
+    pub verify_token: String,
+    pub subscription_id: Option<i64>,
+}
+
+impl WebhookConfig {
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            verify_token: std::env::var("STRAVA_WEBHOOK_VERIFY_TOKEN").ok()?,
+            subscription_id: std::env::var("STRAVA_WEBHOOK_SUBSCRIPTION_ID")
+                .ok()
+                .and_then(|v| v.parse().ok()),
+        })
+    }
+}
+
+pub struct WebhookController {
+    config: Option<WebhookConfig>,
+    sender: mpsc::Sender<WebhookEvent>,
+}
+
+impl WebhookController {
+    /// Creates the controller and spawns the worker that drains the event queue
+    pub fn new(config: Option<WebhookConfig>, activity_controller: Arc<ActivityController>) -> Self {
+        let (sender, receiver) = mpsc::channel(WEBHOOK_QUEUE_SIZE);
+        tokio::spawn(Self::run_worker(receiver, activity_controller));
+
+        WebhookController { config, sender }
+    }
+
+    pub fn verify_challenge(&self, challenge: &SubscriptionChallenge) -> Result<(), ApiError> {
+        let config = self.get_config()?;
+        if challenge.mode != "subscribe" {
+            return Err(ApiError::BadRequest(format!("Unexpected hub.mode: {}", challenge.mode)));
+        }
+        if challenge.verify_token != config.verify_token {
+            println!("[WEBHOOK] verify_challenge: Rejected subscription with invalid verify token");
+            return Err(ApiError::Unauthorized("Invalid verify token".to_string()));
+        }
+        Ok(())
+    }
+
+    pub fn enqueue(&self, event: WebhookEvent) -> Result<(), ApiError> {
+        let config = self.get_config()?;
+        // The receiver is public, so the subscription id is the only proof an event came from our subscription
+        let Some(subscription_id) = config.subscription_id else {
+            println!("[WEBHOOK] enqueue: Rejected event, STRAVA_WEBHOOK_SUBSCRIPTION_ID is not configured");
+            return Err(ApiError::Unauthorized("Webhook events are disabled until the subscription id is configured".to_string()));
+        };
+        if event.subscription_id != Some(subscription_id) {
+            println!("[WEBHOOK] enqueue: Rejected event for unknown subscription {:?}", event.subscription_id);
+            return Err(ApiError::Unauthorized("Unknown subscription".to_string()));
+        }
+
+        if event.object_type != "activity" {
+            println!("[WEBHOOK] enqueue: Ignoring {} event for {}", event.aspect_type, event.object_type);
+            return Ok(());
+        }
+
+        println!("[WEBHOOK] enqueue: Queueing {} event for activity {}", event.aspect_type, event.object_id);
+        self.sender
+            .try_send(event)
+            .map_err(|e| ApiError::InternalConversionError(format!("Webhook queue is unavailable: {}", e)))
+    }
+
+    fn get_config(&self) -> Result<&WebhookConfig, ApiError> {
+        self.config
+            .as_ref()
+            .ok_or_else(|| ApiError::StartupError("STRAVA_WEBHOOK_VERIFY_TOKEN is not configured".to_string()))
+    }
+
+    async fn run_worker(mut receiver: mpsc::Receiver<WebhookEvent>, activity_controller: Arc<ActivityController>) {
+        while let Some(event) = receiver.recv().await {
+            let result = match event.aspect_type.as_str() {
+                "create" | "update" => activity_controller.upsert_strava_activity(event.owner_id, event.object_id).await,
+                "delete" => activity_controller.delete_strava_activity(event.owner_id, event.object_id).await,
+                other => {
+                    println!("[WEBHOOK] run_worker: Ignoring unknown aspect type {}", other);
+                    Ok(())
+                }
+            };
+
+            if let Err(e) = result {
+                eprintln!("[WEBHOOK] run_worker: Failed to process {} event for activity {}: {:?}", event.aspect_type, event.object_id, e);
+            }
+        }
+    }
+}
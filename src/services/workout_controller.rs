@@ -0,0 +1,134 @@
+use std::{collections::HashMap, sync::Arc};
+
+use chrono::{DateTime, Utc};
+
+use crate::{error::ApiError, models::{workout::{CompetitionWorkoutBreakdown, LongRunCount, RaceResult, TeamWorkoutBreakdown, WorkoutCategory, WorkoutQuery, WorkoutVolume}, team::Team}, services::database::Database, utils::time_utils};
+
+// Order categories appear in team breakdowns, easiest first
+const CATEGORY_ORDER: [WorkoutCategory; 4] = [WorkoutCategory::Default, WorkoutCategory::LongRun, WorkoutCategory::Workout, WorkoutCategory::Race];
+
+pub struct WorkoutController {
+    db: Arc<Database>,
+}
+
+impl WorkoutController {
+    pub fn new(db: Arc<Database>) -> Self {
+        WorkoutController { db }
+    }
+
+    pub async fn get_race_results(&self, query: WorkoutQuery) -> Result<Vec<RaceResult>, ApiError> {
+        let (start, end) = Self::get_window(&query)?;
+        let team = query.team.as_deref().map(Team::normalize_id);
+        let races = self.db.get_roster_activities_by_workout_type(start, end, WorkoutCategory::Race.get_strava_codes(), team.as_deref(), query.athlete_id.as_deref()).await?;
+
+        let results = races
+            .into_iter()
+            .map(|race| RaceResult {
+                activity_id: race.activity.id,
+                date: race.activity.date,
+                name: race.activity.name,
+                athlete_id: race.activity.athlete_id.unwrap_or_default(),
+                athlete_name: race.athlete_name,
+                team: race.team,
+                sport_type: race.activity.sport_type,
+                distance_km: race.activity.distance.unwrap_or(0.0) / 1000.0,
+                moving_time_seconds: race.activity.moving_time.unwrap_or(0),
+                elapsed_time_seconds: race.activity.elapsed_time.unwrap_or(0),
+                pace_seconds_per_km: race.activity.metrics.pace_seconds_per_km,
+            })
+            .collect();
+
+        Ok(results)
+    }
+
+    /// Athletes with at least one long run, most long runs first
+    pub async fn get_long_run_counts(&self, query: WorkoutQuery) -> Result<Vec<LongRunCount>, ApiError> {
+        let (start, end) = Self::get_window(&query)?;
+        let team = query.team.as_deref().map(Team::normalize_id);
+        let totals = self.db.get_athlete_totals(start, end, team.as_deref(), None, Some(WorkoutCategory::LongRun.get_strava_codes())).await?;
+
+        let mut counts: Vec<LongRunCount> = totals
+            .into_iter()
+            .filter(|totals| query.athlete_id.as_ref().is_none_or(|athlete_id| *athlete_id == totals.athlete_id))
+            .map(|totals| LongRunCount {
+                long_run_count: totals.activity_count,
+                long_run_distance_km: totals.distance / 1000.0,
+                longest_long_run_km: totals.longest_distance / 1000.0,
+                athlete_id: totals.athlete_id,
+                athlete_name: totals.athlete_name,
+                team: totals.team,
+            })
+            .collect();
+        counts.sort_by(|a, b| b.long_run_count.cmp(&a.long_run_count).then_with(|| a.athlete_name.cmp(&b.athlete_name)));
+
+        Ok(counts)
+    }
+
+    /// Easy, long run, workout and race volume per team over a competition, defaulting to the current one
+    pub async fn get_competition_breakdown(&self, competition_id: Option<&str>) -> Result<CompetitionWorkoutBreakdown, ApiError> {
+        let competition = match competition_id {
+            Some(id) => self.db.read_competition(id).await?
+                .ok_or_else(|| ApiError::NotFound(format!("No competition found with id: {}", id)))?,
+            None => self.db.read_current_competition().await?
+                .ok_or_else(|| ApiError::NotFound("No competition has started yet".to_string()))?,
+        };
+        let (start, end) = competition.get_window();
+        let volume = self.db.get_team_workout_volume(start, end, &competition.sport_types).await?;
+
+        let mut team_volume: HashMap<String, HashMap<WorkoutCategory, (i64, f64, i64)>> = HashMap::new();
+        for row in volume {
+            let category = team_volume
+                .entry(row.team)
+                .or_default()
+                .entry(WorkoutCategory::from_strava(row.workout_type))
+                .or_insert((0, 0.0, 0));
+            category.0 += row.activity_count;
+            category.1 += row.distance;
+            category.2 += row.moving_time;
+        }
+
+        let teams = competition.teams
+            .iter()
+            .map(|team| {
+                let volume = team_volume.remove(team).unwrap_or_default();
+                let team_distance: f64 = volume.values().map(|(_, distance, _)| distance).sum();
+                let categories = CATEGORY_ORDER
+                    .iter()
+                    .map(|category| {
+                        let (activity_count, distance, moving_time) = volume.get(category).copied().unwrap_or((0, 0.0, 0));
+                        WorkoutVolume {
+                            category: *category,
+                            activity_count,
+                            distance_km: distance / 1000.0,
+                            moving_time_seconds: moving_time,
+                            distance_share: if team_distance > 0.0 { distance / team_distance } else { 0.0 },
+                        }
+                    })
+                    .collect();
+                TeamWorkoutBreakdown {
+                    team: team.clone(),
+                    categories,
+                }
+            })
+            .collect();
+
+        Ok(CompetitionWorkoutBreakdown {
+            competition_id: competition.id,
+            start,
+            end,
+            teams,
+        })
+    }
+
+    fn get_window(query: &WorkoutQuery) -> Result<(DateTime<Utc>, DateTime<Utc>), ApiError> {
+        let start = match &query.start {
+            Some(start) => time_utils::parse_datetime("start", start)?,
+            None => DateTime::<Utc>::UNIX_EPOCH,
+        };
+        let end = match &query.end {
+            Some(end) => time_utils::parse_datetime("end", end)?,
+            None => Utc::now(),
+        };
+        Ok((start, end))
+    }
+}
@@ -0,0 +1,26 @@
+use axum::http::HeaderMap;
+
+use crate::error::ApiError;
+
+/// Admin-only routes require the `X-Admin-Token` header to match `ADMIN_SECRET`.
+/// Unlike the populate cron secret, an unset `ADMIN_SECRET` disables these routes entirely.
+pub fn verify_admin_token(headers: &HeaderMap) -> Result<(), ApiError> {
+    let admin_secret = std::env::var("ADMIN_SECRET")
+        .unwrap_or_else(|_| "".to_string());
+
+    if admin_secret.is_empty() {
+        return Err(ApiError::Unauthorized("Admin API is disabled".to_string()));
+    }
+
+    let auth_header = headers
+        .get("X-Admin-Token")
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("");
+
+    if auth_header != admin_secret {
+        println!("Unauthorized admin request");
+        return Err(ApiError::Unauthorized("Invalid admin token".to_string()));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use axum::{body::{Body, Bytes}, http::{HeaderValue, header}, response::{IntoResponse, Response}};
+use chrono::{DateTime, Duration, Utc};
+use futures_util::StreamExt;
+use tokio::sync::mpsc;
+
+use crate::{error::ApiError, models::{bullshark::DateConfidence, calendar::CalendarRace, export::ActivityExport}, utils::time_utils};
+
+const CALENDAR_CONTENT_TYPE: &str = "text/calendar; charset=utf-8";
+// RFC 5545 limits content lines to 75 octets, excluding the line break
+const MAX_LINE_OCTETS: usize = 75;
+const UID_DOMAIN: &str = "bullsharks";
+
+/*
+Streams a VCALENDAR with the races first, then activities as they arrive from `receiver`.
+Like exports, an error mid-stream ends the body early because the status is already sent.
+*/
+pub fn stream_calendar(races: Vec<CalendarRace>, receiver: mpsc::Receiver<Result<ActivityExport, ApiError>>) -> Response {
+    let now = Utc::now();
+    let preamble = [
+        "BEGIN:VCALENDAR",
+        "VERSION:2.0",
+        "PRODID:-//BullSharks//Club Activities//EN",
+        "CALSCALE:GREGORIAN",
+        "METHOD:PUBLISH",
+        "X-WR-CALNAME:BullSharks",
+        "X-WR-TIMEZONE:America/Los_Angeles",
+    ]
+    .iter()
+    .map(|line| format_line(line))
+    .collect::<String>();
+
+    let races = races
+        .iter()
+        .map(|race| format_race(race, now))
+        .collect::<String>();
+
+    let activities = futures_util::stream::unfold(receiver, |mut receiver| async move {
+        match receiver.recv().await? {
+            Ok(export) => Some((Ok(Bytes::from(format_activity(&export))), receiver)),
+            Err(e) => {
+                eprintln!("[CALENDAR] stream_calendar: ending calendar early: {:?}", e);
+                Some((Err(std::io::Error::other(format!("{:?}", e))), receiver))
+            }
+        }
+    });
+
+    let body = futures_util::stream::iter([Ok::<Bytes, std::io::Error>(Bytes::from(preamble + &races))])
+        .chain(activities)
+        .chain(futures_util::stream::iter([Ok(Bytes::from(format_line("END:VCALENDAR")))]));
+
+    let mut response = Body::from_stream(body).into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(CALENDAR_CONTENT_TYPE));
+    headers.insert(header::CONTENT_DISPOSITION, HeaderValue::from_static("inline; filename=\"bullsharks.ics\""));
+    response
+}
+
+/// A timed VEVENT lasting the activity's elapsed time, with a UID from the activity's hash id
+fn format_activity(export: &ActivityExport) -> String {
+    let activity = &export.activity;
+    let start = activity.date.with_timezone(&Utc);
+    let duration = activity.elapsed_time.or(activity.moving_time).unwrap_or(0).max(0);
+
+    let summary = match (&activity.athlete_name, &activity.name) {
+        (Some(athlete_name), Some(name)) => format!("{}: {}", athlete_name, name),
+        (Some(athlete_name), None) => athlete_name.clone(),
+        (None, Some(name)) => name.clone(),
+        (None, None) => "Activity".to_string(),
+    };
+
+    let mut details: Vec<String> = Vec::new();
+    if let Some(sport_type) = &activity.sport_type {
+        details.push(sport_type.clone());
+    }
+    if let Some(distance) = activity.distance.filter(|distance| *distance > 0.0) {
+        details.push(format!("{:.2} km", distance / 1000.0));
+    }
+    if let Some(pace) = activity.metrics.pace_seconds_per_km {
+        details.push(time_utils::format_pace(pace));
+    }
+    if let Some(elevation_gain) = activity.total_elevation_gain.filter(|gain| *gain > 0.0) {
+        details.push(format!("{:.0} m climbed", elevation_gain));
+    }
+    if let Some(team) = &export.team {
+        details.push(format!("Team {}", team));
+    }
+    if activity.date_confidence != DateConfidence::Exact {
+        details.push("Start time estimated".to_string());
+    }
+
+    [
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@{}", activity.id, UID_DOMAIN),
+        format!("DTSTAMP:{}", format_datetime(activity.first_seen_at.with_timezone(&Utc))),
+        format!("DTSTART:{}", format_datetime(start)),
+        format!("DTEND:{}", format_datetime(start + Duration::seconds(duration))),
+        format!("SUMMARY:{}", escape_text(&summary)),
+        format!("DESCRIPTION:{}", escape_text(&details.join("\n"))),
+        "END:VEVENT".to_string(),
+    ]
+    .iter()
+    .map(|line| format_line(line))
+    .collect()
+}
+
+/// An all-day VEVENT on race day, listing the athletes training for it
+fn format_race(race: &CalendarRace, now: DateTime<Utc>) -> String {
+    let Some(date) = race.event.date else {
+        return String::new();
+    };
+
+    let mut description = format!("Training: {}", race.athlete_names.join(", "));
+    if let Some(distance) = race.event.distance {
+        description = format!("{:.2} km\n{}", distance / 1000.0, description);
+    }
+
+    [
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:event-{}@{}", race.event.id, UID_DOMAIN),
+        format!("DTSTAMP:{}", format_datetime(now)),
+        format!("DTSTART;VALUE=DATE:{}", date.format("%Y%m%d")),
+        format!("DTEND;VALUE=DATE:{}", (date + Duration::days(1)).format("%Y%m%d")),
+        format!("SUMMARY:{}", escape_text(&race.event.name)),
+        format!("DESCRIPTION:{}", escape_text(&description)),
+        "TRANSP:TRANSPARENT".to_string(),
+        "END:VEVENT".to_string(),
+    ]
+    .iter()
+    .map(|line| format_line(line))
+    .collect()
+}
+
+fn format_datetime(date: DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Escapes a TEXT value; names come straight from Strava and can contain anything
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\n', '\r'], "\\n")
+}
+
+/// Terminates a content line with CRLF, folding it onto continuation lines without splitting a character
+fn format_line(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + 2);
+    let mut line_octets = 0;
+    for character in line.chars() {
+        // Continuation lines start with a space, which counts towards their length
+        if line_octets + character.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            line_octets = 1;
+        }
+        folded.push(character);
+        line_octets += character.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
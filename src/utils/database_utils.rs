@@ -1,4 +1,6 @@
-use crate::models::oauth::StravaAuthToken;
+use crate::models::{athlete::Athlete, effort::ActivityMetrics, workout::WorkoutCategory, competition::Competition, event::RaceEvent, goal::{Goal, GoalMetric, GoalPeriod}, scoring::ScoringConfig, bullshark::{BullSharkActivity, DateConfidence}, invite::StravaInvite, oauth::StravaAuthToken};
+use chrono::{DateTime, FixedOffset, Offset, TimeZone, Utc};
+use chrono_tz::America::Los_Angeles;
 use sqlx::Row;
 
 /// Helper to map a database row to StravaAuthToken
@@ -13,4 +15,110 @@ pub fn map_row_to_token(row: sqlx::postgres::PgRow) -> StravaAuthToken {
         expires_in: row.get("expires_in"),
         refresh_token: row.get("refresh_token"),
     }
-}
\ No newline at end of file
+}
+
+/// Helper to map a strava_invites row to StravaInvite
+pub fn map_row_to_strava_invite(row: sqlx::postgres::PgRow) -> StravaInvite {
+    StravaInvite {
+        token: row.get("token"),
+        athlete_id: row.get("athlete_id"),
+        replace_strava_id: row.get("replace_strava_id"),
+        expires_at: row.get("expires_at"),
+        used_at: row.get("used_at"),
+    }
+}
+
+/// Helper to map a bullshark_activities row to BullSharkActivity, with dates in Pacific time
+pub fn map_row_to_activity(row: sqlx::postgres::PgRow) -> BullSharkActivity {
+    let date_confidence: String = row.get("date_confidence");
+    let distance: Option<f64> = row.get("distance");
+    let moving_time: Option<i64> = row.get("moving_time");
+    let elapsed_time: Option<i64> = row.get("elapsed_time");
+    let total_elevation_gain: Option<f64> = row.get("total_elevation_gain");
+    let workout_type: Option<i64> = row.get("workout_type");
+
+    BullSharkActivity {
+        id: row.get("id"),
+        strava_activity_id: row.get("strava_activity_id"),
+        date: to_pacific(row.get("date")),
+        first_seen_at: to_pacific(row.get("first_seen_at")),
+        date_confidence: DateConfidence::from_db(&date_confidence),
+        athlete_name: row.get("athlete_name"),
+        athlete_id: row.get("athlete_id"),
+        resource_state: row.get("resource_state"),
+        name: row.get("name"),
+        distance,
+        moving_time,
+        elapsed_time,
+        total_elevation_gain,
+        sport_type: row.get("sport_type"),
+        workout_type,
+        workout_category: WorkoutCategory::from_strava(workout_type),
+        device_name: row.get("device_name"),
+        metrics: ActivityMetrics::new(distance, moving_time, elapsed_time, total_elevation_gain),
+    }
+}
+
+/// Timestamps are stored as UTC; convert to Pacific as a FixedOffset for serialization support
+pub fn to_pacific(date_utc: DateTime<Utc>) -> DateTime<FixedOffset> {
+    let date_pacific_tz = Los_Angeles.from_utc_datetime(&date_utc.naive_utc());
+    date_pacific_tz.with_timezone(&date_pacific_tz.offset().fix())
+}
+
+/// Helper to map an athletes row to Athlete
+pub fn map_row_to_athlete(row: sqlx::postgres::PgRow) -> Athlete {
+    Athlete {
+        id: row.get("id"),
+        name: row.get("name"),
+        team: row.get("team"),
+        event: row.get("event"),
+        strava_id: row.get("strava_id"),
+    }
+}
+
+/// Helper to map a competitions row to Competition
+pub fn map_row_to_competition(row: sqlx::postgres::PgRow) -> Competition {
+    Competition {
+        id: row.get("id"),
+        name: row.get("name"),
+        start_date: row.get("start_date"),
+        end_date: row.get("end_date"),
+        timezone: row.get("timezone"),
+        teams: row.get("teams"),
+        sport_types: row.get("sport_types"),
+        scoring: parse_scoring(row.get("scoring")),
+    }
+}
+
+/// Helper to map an events row to RaceEvent
+pub fn map_row_to_event(row: sqlx::postgres::PgRow) -> RaceEvent {
+    RaceEvent {
+        id: row.get("id"),
+        name: row.get("name"),
+        date: row.get("date"),
+        distance: row.get("distance"),
+    }
+}
+
+/// Helper to map a goals row to Goal
+pub fn map_row_to_goal(row: sqlx::postgres::PgRow) -> Goal {
+    Goal {
+        id: row.get("id"),
+        athlete_id: row.get("athlete_id"),
+        metric: GoalMetric::from_db(row.get("metric")),
+        period: GoalPeriod::from_db(row.get("period")),
+        target: row.get("target"),
+        competition_id: row.get("competition_id"),
+        sport_types: row.get("sport_types"),
+        created_at: row.get("created_at"),
+        archived_at: row.get("archived_at"),
+    }
+}
+
+// scoring is read as text (scoring::text) since sqlx isn't built with JSON support
+fn parse_scoring(scoring: String) -> ScoringConfig {
+    serde_json::from_str(&scoring).unwrap_or_else(|e| {
+        eprintln!("Invalid competition scoring config, falling back to distance: {}", e);
+        ScoringConfig::default()
+    })
+}
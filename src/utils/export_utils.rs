@@ -0,0 +1,93 @@
+use axum::{body::{Body, Bytes}, http::{HeaderMap, HeaderValue, header}, response::{IntoResponse, Response}};
+use serde::Serialize;
+use tokio::sync::mpsc;
+
+use crate::{error::ApiError, models::export::ExportFormat};
+
+/// `format=` wins over the Accept header, and CSV is the default
+pub fn get_export_format(format: Option<&str>, headers: &HeaderMap) -> Result<ExportFormat, ApiError> {
+    if let Some(format) = format {
+        return match format.trim().to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "ndjson" | "jsonl" => Ok(ExportFormat::Ndjson),
+            other => Err(ApiError::BadRequest(format!("Unknown export format: {} (expected csv or ndjson)", other))),
+        };
+    }
+
+    let accept = headers
+        .get(header::ACCEPT)
+        .and_then(|h| h.to_str().ok())
+        .unwrap_or("")
+        .to_lowercase();
+    if accept.contains("ndjson") || accept.contains("jsonl") {
+        Ok(ExportFormat::Ndjson)
+    } else {
+        Ok(ExportFormat::Csv)
+    }
+}
+
+/*
+Streams rows from `receiver` as the response body, encoding each one as it arrives.
+CSV gets its header from the first row, so an empty export is an empty body.
+`to_csv` converts rows whose JSON shape doesn't flatten into columns.
+An error mid-stream can't change the status code any more, so it ends the body early instead.
+*/
+pub fn stream_export<T, C>(receiver: mpsc::Receiver<Result<T, ApiError>>, format: ExportFormat, filename: &str, to_csv: fn(T) -> C) -> Response
+where
+    T: Serialize + Send + 'static,
+    C: Serialize + 'static,
+{
+    let body = futures_util::stream::unfold((receiver, true), move |(mut receiver, first)| async move {
+        let row = receiver.recv().await?;
+        let encoded = row.and_then(|row| match format {
+            ExportFormat::Csv => encode_csv(&to_csv(row), first),
+            ExportFormat::Ndjson => encode_ndjson(&row),
+        });
+
+        match encoded {
+            Ok(bytes) => Some((Ok::<Bytes, std::io::Error>(bytes), (receiver, false))),
+            Err(e) => {
+                eprintln!("[EXPORT] stream_export: ending export early: {:?}", e);
+                Some((Err(std::io::Error::other(format!("{:?}", e))), (receiver, false)))
+            }
+        }
+    });
+
+    let mut response = Body::from_stream(body).into_response();
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(format.get_content_type()));
+    if let Ok(disposition) = HeaderValue::from_str(&format!("attachment; filename=\"{}.{}\"", filename, format.get_extension())) {
+        headers.insert(header::CONTENT_DISPOSITION, disposition);
+    }
+    response
+}
+
+/// Feeds rows that were computed up front to `stream_export`
+pub fn get_row_receiver<T>(rows: Vec<T>) -> mpsc::Receiver<Result<T, ApiError>> {
+    let (sender, receiver) = mpsc::channel(rows.len().max(1));
+    for row in rows {
+        // The channel holds every row, so this never fails
+        let _ = sender.try_send(Ok(row));
+    }
+    receiver
+}
+
+fn encode_csv<C: Serialize>(row: &C, with_header: bool) -> Result<Bytes, ApiError> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(with_header)
+        .from_writer(Vec::new());
+    writer
+        .serialize(row)
+        .map_err(|e| ApiError::InternalConversionError(format!("Failed to write CSV row: {}", e)))?;
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| ApiError::InternalConversionError(format!("Failed to flush CSV row: {}", e)))?;
+    Ok(Bytes::from(bytes))
+}
+
+fn encode_ndjson<T: Serialize>(row: &T) -> Result<Bytes, ApiError> {
+    let mut bytes = serde_json::to_vec(row)
+        .map_err(|e| ApiError::InternalConversionError(format!("Failed to write NDJSON row: {}", e)))?;
+    bytes.push(b'\n');
+    Ok(Bytes::from(bytes))
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeSet;
+
+use axum::{http::{HeaderValue, header}, response::{IntoResponse, Response}};
+use chrono::{DateTime, Duration, Utc};
+use chrono_tz::Tz;
+
+use crate::{models::{bullshark::BullSharkActivity, feed::{AtomEntry, AtomFeed}, team_stats::MultiTeamStats}, utils::time_utils};
+
+const ATOM_CONTENT_TYPE: &str = "application/atom+xml; charset=utf-8";
+const STRAVA_ACTIVITY_URL: &str = "https://www.strava.com/activities";
+
+/// One team's line in a weekly results entry
+struct TeamWeek<'a> {
+    display_name: &'a str,
+    kilometers: f64,
+    running_sum: f64,
+    top_athlete: Option<(&'a str, f64)>,
+}
+
+pub fn atom_response(feed: &AtomFeed) -> Response {
+    let mut response = render_atom(feed).into_response();
+    response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static(ATOM_CONTENT_TYPE));
+    response
+}
+
+/// Activities newest first, updated when we first saw them
+pub fn get_activity_entries(activities: &[BullSharkActivity]) -> Vec<AtomEntry> {
+    activities
+        .iter()
+        .map(|activity| {
+            let title = match (&activity.athlete_name, &activity.name) {
+                (Some(athlete_name), Some(name)) => format!("{}: {}", athlete_name, name),
+                (Some(athlete_name), None) => athlete_name.clone(),
+                (None, Some(name)) => name.clone(),
+                (None, None) => "Activity".to_string(),
+            };
+            let date = activity.date.with_timezone(&Utc);
+
+            AtomEntry {
+                id: format!("urn:bullsharks:activity:{}", activity.id),
+                title,
+                published: Some(date),
+                updated: activity.first_seen_at.with_timezone(&Utc).max(date),
+                summary: get_activity_summary(activity),
+                link: activity.strava_activity_id.map(|id| format!("{}/{}", STRAVA_ACTIVITY_URL, id)),
+            }
+        })
+        .collect()
+}
+
+/// e.g. "Run · 10.20 km · 5:07 /km · 85 m elevation"
+fn get_activity_summary(activity: &BullSharkActivity) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(sport_type) = &activity.sport_type {
+        parts.push(sport_type.clone());
+    }
+    if let Some(distance) = activity.distance.filter(|distance| *distance > 0.0) {
+        parts.push(format!("{:.2} km", distance / 1000.0));
+    }
+    if let Some(pace) = activity.metrics.pace_seconds_per_km {
+        parts.push(time_utils::format_pace(pace));
+    }
+    if let Some(elevation_gain) = activity.total_elevation_gain.filter(|gain| *gain > 0.0) {
+        parts.push(format!("{:.0} m elevation", elevation_gain));
+    }
+    if parts.is_empty() {
+        parts.push("No stats recorded".to_string());
+    }
+    parts.join(" · ")
+}
+
+/*
+One entry per finished competition week, newest first. Weeks only exist in the stats where a team ran,
+so the union across teams is used and a team without a week shows zero kilometres for it.
+The week still in progress is left out so readers don't see the same entry change all week.
+*/
+pub fn get_team_week_entries(stats: &MultiTeamStats, timezone: &Tz, now: DateTime<Utc>) -> Vec<AtomEntry> {
+    let week_starts: BTreeSet<DateTime<Utc>> = stats.teams
+        .values()
+        .flat_map(|team| team.data.weekly_kilometers.iter().map(|week| week.week_start.with_timezone(&Utc)))
+        .collect();
+
+    week_starts
+        .into_iter()
+        .rev()
+        .filter(|week_start| *week_start + Duration::weeks(1) <= now)
+        .map(|week_start| {
+            let mut results: Vec<TeamWeek> = stats.teams
+                .values()
+                .map(|team| {
+                    let week = team.data.weekly_kilometers.iter().find(|week| week.week_start.with_timezone(&Utc) == week_start);
+                    // Weeks without a row of their own carry the last running sum forward
+                    let running_sum = team.data.weekly_kilometers
+                        .iter()
+                        .filter(|week| week.week_start.with_timezone(&Utc) <= week_start)
+                        .map(|week| week.weekly_running_sum)
+                        .fold(0.0, f64::max);
+                    let top_athlete = week.and_then(|week| week.weekly_athlete_kilometers
+                        .iter()
+                        .max_by(|a, b| a.1.total_cmp(b.1))
+                        .map(|(name, kilometers)| (name.as_str(), *kilometers)));
+                    TeamWeek {
+                        display_name: &team.display_name,
+                        kilometers: week.map_or(0.0, |week| week.weekly_team_kilometers),
+                        running_sum,
+                        top_athlete,
+                    }
+                })
+                .collect();
+            results.sort_by(|a, b| b.kilometers.total_cmp(&a.kilometers).then_with(|| a.display_name.cmp(b.display_name)));
+
+            let week_label = week_start.with_timezone(timezone).format("%b %-d, %Y");
+            let title = format!(
+                "Week of {}: {}",
+                week_label,
+                results.iter().map(|team| format!("{} {:.1} km", team.display_name, team.kilometers)).collect::<Vec<String>>().join(", ")
+            );
+            let summary = results
+                .iter()
+                .map(|team| {
+                    let mut line = format!("{}: {:.1} km this week, {:.1} km total", team.display_name, team.kilometers, team.running_sum);
+                    if let Some((name, athlete_kilometers)) = team.top_athlete {
+                        line.push_str(&format!(". Top athlete {} with {:.1} km", name, athlete_kilometers));
+                    }
+                    line
+                })
+                .collect::<Vec<String>>()
+                .join("\n");
+
+            AtomEntry {
+                id: format!("urn:bullsharks:competition:{}:week:{}", stats.competition_id, week_start.format("%Y-%m-%d")),
+                title,
+                published: None,
+                updated: week_start + Duration::weeks(1),
+                summary,
+                link: None,
+            }
+        })
+        .collect()
+}
+
+/// The feed is updated whenever its newest entry is, or now when it has none
+pub fn get_feed_updated(entries: &[AtomEntry], now: DateTime<Utc>) -> DateTime<Utc> {
+    entries.iter().map(|entry| entry.updated).max().unwrap_or(now)
+}
+
+fn render_atom(feed: &AtomFeed) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <id>{}</id>\n", escape_xml(&feed.id)));
+    xml.push_str(&format!("  <title>{}</title>\n", escape_xml(&feed.title)));
+    xml.push_str(&format!("  <updated>{}</updated>\n", feed.updated.to_rfc3339()));
+    xml.push_str("  <author><name>BullSharks</name></author>\n");
+
+    for entry in &feed.entries {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!("    <id>{}</id>\n", escape_xml(&entry.id)));
+        xml.push_str(&format!("    <title>{}</title>\n", escape_xml(&entry.title)));
+        if let Some(published) = entry.published {
+            xml.push_str(&format!("    <published>{}</published>\n", published.to_rfc3339()));
+        }
+        xml.push_str(&format!("    <updated>{}</updated>\n", entry.updated.to_rfc3339()));
+        if let Some(link) = &entry.link {
+            xml.push_str(&format!("    <link rel=\"alternate\" href=\"{}\"/>\n", escape_xml(link)));
+        }
+        xml.push_str(&format!("    <summary type=\"text\">{}</summary>\n", escape_xml(&entry.summary)));
+        xml.push_str(&format!("    <content type=\"text\">{}</content>\n", escape_xml(&entry.summary)));
+        xml.push_str("  </entry>\n");
+    }
+
+    xml.push_str("</feed>\n");
+    xml
+}
+
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        // Control characters other than tab and newlines aren't allowed anywhere in XML 1.0
+        .filter(|character| !character.is_control() || matches!(character, '\t' | '\n' | '\r'))
+        .fold(String::with_capacity(text.len()), |mut escaped, character| {
+            match character {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                _ => escaped.push(character),
+            }
+            escaped
+        })
+}
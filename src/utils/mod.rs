@@ -1,2 +1,8 @@
 pub mod startup_utils;
 pub mod database_utils;
+pub mod auth_utils;
+pub mod roster_utils;
+pub mod time_utils;
+pub mod export_utils;
+pub mod calendar_utils;
+pub mod feed_utils;
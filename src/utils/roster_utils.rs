@@ -0,0 +1,153 @@
+use std::collections::HashSet;
+
+use crate::models::{athlete::{Athlete, AthleteInput, RosterRowError}, event::RaceEvent, team::Team};
+
+const REQUIRED_COLUMNS: [&str; 4] = ["id", "name", "team", "event"];
+
+/*
+Parses a roster CSV with an `id,name,team,event` header (any column order).
+Every row is validated on its own so one bad row doesn't reject the whole upload.
+*/
+pub fn parse_roster_csv(body: &str, teams: &[Team], events: &[RaceEvent]) -> Result<(Vec<Athlete>, Vec<RosterRowError>), String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(body.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| format!("Could not read CSV header: {}", e))?
+        .clone();
+
+    let mut column_indexes = Vec::with_capacity(REQUIRED_COLUMNS.len());
+    for column in REQUIRED_COLUMNS {
+        let index = headers
+            .iter()
+            .position(|header| header.eq_ignore_ascii_case(column))
+            .ok_or_else(|| format!("CSV header is missing the '{}' column", column))?;
+        column_indexes.push(index);
+    }
+
+    let mut athletes: Vec<Athlete> = Vec::new();
+    let mut errors: Vec<RosterRowError> = Vec::new();
+    let mut seen_ids: HashSet<String> = HashSet::new();
+
+    for record in reader.records() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(RosterRowError {
+                    row: e.position().map(|p| p.line()).unwrap_or(0),
+                    id: None,
+                    error: format!("Malformed row: {}", e),
+                });
+                continue;
+            }
+        };
+        let row = record.position().map(|p| get_line(body, p)).unwrap_or(0);
+        let field = |index: usize| record.get(column_indexes[index]).unwrap_or("").to_string();
+
+        let input = AthleteInput {
+            id: field(0),
+            name: field(1),
+            team: field(2),
+            event: field(3),
+        };
+        let id = Some(input.id.clone()).filter(|id| !id.is_empty());
+
+        match input.into_athlete(teams, events) {
+            Ok(athlete) if !seen_ids.insert(athlete.id.clone()) => errors.push(RosterRowError {
+                row,
+                id,
+                error: format!("Duplicate athlete id {} in upload", athlete.id),
+            }),
+            Ok(athlete) => athletes.push(athlete),
+            Err(error) => errors.push(RosterRowError { row, id, error }),
+        }
+    }
+
+    Ok((athletes, errors))
+}
+
+// csv reports the line its read started on, which is the first blank line before the record if there are any
+fn get_line(body: &str, position: &csv::Position) -> u64 {
+    let skipped_lines = body.as_bytes()
+        .get(position.byte() as usize..)
+        .unwrap_or_default()
+        .iter()
+        .take_while(|byte| matches!(byte, b'\r' | b'\n'))
+        .filter(|byte| **byte == b'\n')
+        .count();
+    position.line() + skipped_lines as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_teams() -> Vec<Team> {
+        vec![Team::fallback("sharks"), Team::fallback("orcas")]
+    }
+
+    fn get_events() -> Vec<RaceEvent> {
+        vec![RaceEvent {
+            id: "marathon".to_string(),
+            name: "Marathon".to_string(),
+            date: None,
+            distance: Some(42195.0),
+        }]
+    }
+
+    fn get_errors(errors: &[RosterRowError]) -> Vec<(u64, Option<&str>, &str)> {
+        errors.iter().map(|error| (error.row, error.id.as_deref(), error.error.as_str())).collect()
+    }
+
+    #[test]
+    fn parses_columns_in_any_order_and_stores_ids() {
+        let body = "Name,Event,ID,Team\nAlex Smith, Marathon ,alex,SHARKS\n";
+        let (athletes, errors) = parse_roster_csv(body, &get_teams(), &get_events()).unwrap();
+
+        assert!(errors.is_empty());
+        assert_eq!(athletes.len(), 1);
+        assert_eq!((athletes[0].id.as_str(), athletes[0].name.as_str()), ("alex", "Alex Smith"));
+        assert_eq!((athletes[0].team.as_str(), athletes[0].event.as_str()), ("sharks", "marathon"));
+    }
+
+    #[test]
+    fn missing_column_rejects_the_upload() {
+        let error = parse_roster_csv("id,name,team\nalex,Alex Smith,sharks\n", &get_teams(), &get_events()).unwrap_err();
+        assert_eq!(error, "CSV header is missing the 'event' column");
+    }
+
+    #[test]
+    fn collects_an_error_per_bad_row() {
+        let body = "\
+id,name,team,event
+alex,Alex Smith,sharks,marathon
+
+sam,Sam Jones,dolphins,marathon
+jo,Jo Brown,orcas,ultra
+alex,Alex Stone,orcas,marathon
+,No Id,orcas,marathon
+kim,Kim Lee,orcas,marathon
+";
+        let (athletes, errors) = parse_roster_csv(body, &get_teams(), &get_events()).unwrap();
+
+        let ids: Vec<&str> = athletes.iter().map(|athlete| athlete.id.as_str()).collect();
+        assert_eq!(ids, vec!["alex", "kim"]);
+        // The blank line is skipped but still counts towards row numbers
+        assert_eq!(get_errors(&errors), vec![
+            (4, Some("sam"), "Unknown team: dolphins"),
+            (5, Some("jo"), "Unknown event: ultra"),
+            (6, Some("alex"), "Duplicate athlete id alex in upload"),
+            (7, None, "Athlete id is required"),
+        ]);
+    }
+
+    #[test]
+    fn rows_after_several_blank_crlf_lines_keep_their_line_number() {
+        let body = "id,name,team,event\r\n\r\n\r\nsam,Sam Jones,dolphins,marathon\r\n";
+        let (_, errors) = parse_roster_csv(body, &get_teams(), &get_events()).unwrap();
+        assert_eq!(get_errors(&errors), vec![(4, Some("sam"), "Unknown team: dolphins")]);
+    }
+}
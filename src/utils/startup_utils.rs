@@ -3,32 +3,65 @@ use std::sync::Arc;
 use axum::{Router, routing::{get, post}, extract::FromRef};
 use sqlx::{PgPool};
 
-use crate::{api::{activities::{get_activities_from_custom_window, get_activities_from_this_month, get_activities_from_this_week, get_team_stats, populate_activities, read_activities}, athletes::get_athletes, health::health_check}, services::{activity_controller::ActivityController, auth_controller::{AuthController, StravaConfig}, database::Database, strava_client::StravaClient}};
+use crate::{api::{activities::{backfill_activity_athletes, get_activities_from_custom_window, get_activities_from_this_month, get_activities_from_this_week, get_all_team_stats, get_team_consistency, get_team_stats, populate_activities, read_activities}, athletes::{bind_athlete_alias, create_athlete, delete_athlete, get_athlete_activities, get_athlete_aliases, get_athlete_profile, get_athletes, get_team_memberships, get_unmatched_athletes, transfer_athlete, update_athlete, upload_athletes_csv}, auth::{create_strava_invite, strava_callback, strava_login}, calendar::get_calendar, feeds::{get_activity_feed, get_team_results_feed}, live::get_live_updates, exports::{export_activities, export_team_stats}, events::{get_event_dashboard, get_events, upsert_event}, goals::{archive_athlete_goal, create_athlete_goal, get_athlete_goal, get_athlete_goals}, competitions::{create_competition, explain_activity_points, get_competition, get_competition_consistency, get_competition_team_stats, get_competitions}, health::health_check, leaderboards::get_leaderboard, teams::{get_teams, upsert_team}, workouts::{get_competition_workout_breakdown, get_current_workout_breakdown, get_long_run_counts, get_race_results}, webhooks::{receive_strava_event, verify_strava_subscription}}, services::{activity_controller::ActivityController, athlete_controller::AthleteController, auth_controller::{AuthController, StravaConfig}, database::Database, date_resolver::DateResolver, event_controller::EventController, goal_controller::GoalController, leaderboard_controller::LeaderboardController, strava_client::StravaClient, strava_http::StravaHttp, webhook_controller::{WebhookConfig, WebhookController}, workout_controller::WorkoutController}};
 
 pub fn get_strava_config() -> StravaConfig {
-    return StravaConfig::from_env()
-        .expect("Failed to find environment variables.");
+    StravaConfig::from_env()
+        .expect("Failed to find environment variables.")
 }
 
-pub fn get_auth_controller(strava_config: StravaConfig, db: Arc<Database>) -> AuthController {
-    return AuthController::new(strava_config, db)
+pub fn get_strava_http() -> Arc<StravaHttp> {
+    let http = StravaHttp::new()
+        .expect("Failed to build the Strava HTTP client");
+    Arc::new(http)
 }
 
-pub fn get_strava_client(auth_controller: AuthController) -> StravaClient {
-    return StravaClient::new(auth_controller)
+pub fn get_auth_controller(strava_config: StravaConfig, db: Arc<Database>, http: Arc<StravaHttp>) -> Arc<AuthController> {
+    Arc::new(AuthController::new(strava_config, db, http))
+}
+
+pub fn get_strava_client(auth_controller: Arc<AuthController>) -> StravaClient {
+    StravaClient::new(auth_controller)
 }
 
 pub fn get_activity_controller(db: Arc<Database>, strava_client: StravaClient) -> ActivityController {
-    ActivityController::new(db, strava_client)
+    let date_resolver = DateResolver::new(db.clone());
+    ActivityController::new(db, strava_client, date_resolver)
+}
+
+pub fn get_webhook_controller(activity_controller: Arc<ActivityController>) -> Arc<WebhookController> {
+    let config = WebhookConfig::from_env();
+    if config.is_none() {
+        println!("STRAVA_WEBHOOK_VERIFY_TOKEN not set, Strava webhooks are disabled");
+    }
+    Arc::new(WebhookController::new(config, activity_controller))
+}
+
+pub fn get_athlete_controller(db: Arc<Database>) -> Arc<AthleteController> {
+    Arc::new(AthleteController::new(db))
+}
+
+pub fn get_workout_controller(db: Arc<Database>) -> Arc<WorkoutController> {
+    Arc::new(WorkoutController::new(db))
+}
+
+pub fn get_goal_controller(db: Arc<Database>) -> Arc<GoalController> {
+    Arc::new(GoalController::new(db))
+}
+
+pub fn get_event_controller(db: Arc<Database>) -> Arc<EventController> {
+    Arc::new(EventController::new(db))
+}
+
+pub fn get_leaderboard_controller(db: Arc<Database>) -> Arc<LeaderboardController> {
+    Arc::new(LeaderboardController::new(db))
 }
 
 pub async fn get_db() -> Arc<Database> {
     let pool = get_pg_pool().await
         .expect("Error: could not create the database connection pool");
 
-    let db = Arc::new(Database::new(pool));
-
-    return db;
+    Arc::new(Database::new(pool))
 }
 
 async fn get_pg_pool() -> Result<PgPool, sqlx::Error> {
@@ -42,11 +75,18 @@ async fn get_pg_pool() -> Result<PgPool, sqlx::Error> {
     Ok(pool)
 }
 
-// AppState holds both Database and ActivityController for routing
+// AppState holds the Database and controllers for routing
 #[derive(Clone)]
 pub struct AppState {
     pub db: Arc<Database>,
     pub activity_controller: Arc<ActivityController>,
+    pub auth_controller: Arc<AuthController>,
+    pub webhook_controller: Arc<WebhookController>,
+    pub leaderboard_controller: Arc<LeaderboardController>,
+    pub athlete_controller: Arc<AthleteController>,
+    pub workout_controller: Arc<WorkoutController>,
+    pub goal_controller: Arc<GoalController>,
+    pub event_controller: Arc<EventController>,
 }
 
 // Allow extracting Database from AppState
@@ -63,6 +103,55 @@ impl FromRef<AppState> for Arc<ActivityController> {
     }
 }
 
+// Allow extracting AuthController from AppState
+impl FromRef<AppState> for Arc<AuthController> {
+    fn from_ref(state: &AppState) -> Arc<AuthController> {
+        state.auth_controller.clone()
+    }
+}
+
+// Allow extracting WebhookController from AppState
+impl FromRef<AppState> for Arc<WebhookController> {
+    fn from_ref(state: &AppState) -> Arc<WebhookController> {
+        state.webhook_controller.clone()
+    }
+}
+
+// Allow extracting LeaderboardController from AppState
+impl FromRef<AppState> for Arc<LeaderboardController> {
+    fn from_ref(state: &AppState) -> Arc<LeaderboardController> {
+        state.leaderboard_controller.clone()
+    }
+}
+
+// Allow extracting AthleteController from AppState
+impl FromRef<AppState> for Arc<AthleteController> {
+    fn from_ref(state: &AppState) -> Arc<AthleteController> {
+        state.athlete_controller.clone()
+    }
+}
+
+// Allow extracting WorkoutController from AppState
+impl FromRef<AppState> for Arc<WorkoutController> {
+    fn from_ref(state: &AppState) -> Arc<WorkoutController> {
+        state.workout_controller.clone()
+    }
+}
+
+// Allow extracting GoalController from AppState
+impl FromRef<AppState> for Arc<GoalController> {
+    fn from_ref(state: &AppState) -> Arc<GoalController> {
+        state.goal_controller.clone()
+    }
+}
+
+// Allow extracting EventController from AppState
+impl FromRef<AppState> for Arc<EventController> {
+    fn from_ref(state: &AppState) -> Arc<EventController> {
+        state.event_controller.clone()
+    }
+}
+
 fn create_app(state: AppState) -> Router {
     Router::new()
         .route("/health", get(health_check))
@@ -71,8 +160,43 @@ fn create_app(state: AppState) -> Router {
         .route("/activities/week", get(get_activities_from_this_week))
         .route("/activities/month", get(get_activities_from_this_month))
         .route("/activities/window", get(get_activities_from_custom_window))
+        .route("/activities/backfill_athletes", post(backfill_activity_athletes))
         .route("/team_stats", get(get_team_stats))
-        .route("/athletes", get(get_athletes))
+        .route("/export/activities", get(export_activities))
+        .route("/export/team_stats", get(export_team_stats))
+        .route("/calendar.ics", get(get_calendar))
+        .route("/feeds/activities.atom", get(get_activity_feed))
+        .route("/feeds/team_results.atom", get(get_team_results_feed))
+        .route("/team_stats/teams", get(get_all_team_stats))
+        .route("/team_stats/workouts", get(get_current_workout_breakdown))
+        .route("/team_stats/consistency", get(get_team_consistency))
+        .route("/team_stats/live", get(get_live_updates))
+        .route("/leaderboards", get(get_leaderboard))
+        .route("/workouts/races", get(get_race_results))
+        .route("/workouts/long_runs", get(get_long_run_counts))
+        .route("/teams", get(get_teams).post(upsert_team))
+        .route("/events", get(get_events).post(upsert_event))
+        .route("/events/:id/dashboard", get(get_event_dashboard))
+        .route("/athletes", get(get_athletes).post(create_athlete))
+        .route("/athletes/bulk", post(upload_athletes_csv))
+        .route("/athletes/unmatched", get(get_unmatched_athletes))
+        .route("/athletes/aliases", get(get_athlete_aliases).post(bind_athlete_alias))
+        .route("/athletes/:id", get(get_athlete_profile).put(update_athlete).delete(delete_athlete))
+        .route("/athletes/:id/activities", get(get_athlete_activities))
+        .route("/athletes/:id/memberships", get(get_team_memberships))
+        .route("/athletes/:id/transfers", post(transfer_athlete))
+        .route("/athletes/:id/goals", get(get_athlete_goals).post(create_athlete_goal))
+        .route("/athletes/:id/goals/:goal_id", get(get_athlete_goal).delete(archive_athlete_goal))
+        .route("/auth/strava/invites", post(create_strava_invite))
+        .route("/auth/strava/login", get(strava_login))
+        .route("/auth/strava/callback", get(strava_callback))
+        .route("/competitions", get(get_competitions).post(create_competition))
+        .route("/competitions/:id", get(get_competition))
+        .route("/competitions/:id/team_stats", get(get_competition_team_stats))
+        .route("/competitions/:id/workouts", get(get_competition_workout_breakdown))
+        .route("/competitions/:id/consistency", get(get_competition_consistency))
+        .route("/competitions/:id/activities/:activity_id/points", get(explain_activity_points))
+        .route("/webhooks/strava", get(verify_strava_subscription).post(receive_strava_event))
         .with_state(state)
 }
 
@@ -104,12 +228,7 @@ async fn shutdown_signal() {
     println!("Shutdown signal received, starting graceful shutdown");
 }
 
-pub async fn create_server(db: Arc<Database>, activity_controller: Arc<ActivityController>) {
-    let state = AppState {
-        db,
-        activity_controller,
-    };
-
+pub async fn create_server(state: AppState) {
     let app = create_app(state);
     let listener = tokio::net::TcpListener::bind("0.0.0.0:8080")
         .await
@@ -0,0 +1,83 @@
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
+use chrono_tz::America::Los_Angeles;
+
+use crate::error::ApiError;
+
+/// The Monday-to-Sunday week containing `reference`, in Pacific time, as an inclusive UTC range
+pub fn get_week_window(reference: DateTime<Utc>) -> Result<(DateTime<Utc>, DateTime<Utc>), ApiError> {
+    let reference_pacific = Los_Angeles.from_utc_datetime(&reference.naive_utc());
+
+    // Calculate start of week (Monday 00:00:00) in Pacific
+    let days_since_monday = reference_pacific.weekday().num_days_from_monday();
+    let start_of_week_pacific = reference_pacific
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .unwrap()
+        - Duration::days(days_since_monday as i64);
+    let start_of_week_pacific = Los_Angeles.from_local_datetime(&start_of_week_pacific).single()
+        .ok_or_else(|| ApiError::InternalConversionError("Invalid start of week time".to_string()))?;
+
+    // Calculate end of week (Sunday 23:59:59) in Pacific
+    let end_of_week_pacific = start_of_week_pacific
+        .date_naive()
+        .and_hms_opt(23, 59, 59)
+        .unwrap()
+        + Duration::days(6);
+    let end_of_week_pacific = Los_Angeles.from_local_datetime(&end_of_week_pacific).single()
+        .ok_or_else(|| ApiError::InternalConversionError("Invalid end of week time".to_string()))?;
+
+    Ok((start_of_week_pacific.with_timezone(&Utc), end_of_week_pacific.with_timezone(&Utc)))
+}
+
+/// The calendar month containing `reference`, in Pacific time, as an inclusive UTC range
+pub fn get_month_window(reference: DateTime<Utc>) -> Result<(DateTime<Utc>, DateTime<Utc>), ApiError> {
+    let reference_pacific = Los_Angeles.from_utc_datetime(&reference.naive_utc());
+
+    // Calculate start of month (1st day at 00:00:00) in Pacific
+    let start_of_month_pacific = reference_pacific
+        .date_naive()
+        .with_day(1)
+        .ok_or_else(|| ApiError::InternalConversionError("Invalid start of month date".to_string()))?
+        .and_hms_opt(0, 0, 0)
+        .unwrap();
+    let start_of_month_pacific = Los_Angeles.from_local_datetime(&start_of_month_pacific).single()
+        .ok_or_else(|| ApiError::InternalConversionError("Invalid start of month time".to_string()))?;
+
+    // Get the first day of next month, then subtract 1 second to get end of current month
+    let next_month = if reference_pacific.month() == 12 {
+        start_of_month_pacific.date_naive()
+            .with_year(reference_pacific.year() + 1)
+            .and_then(|d| d.with_month(1))
+    } else {
+        start_of_month_pacific.date_naive()
+            .with_month(reference_pacific.month() + 1)
+    }
+    .ok_or_else(|| ApiError::InternalConversionError("Invalid next month date".to_string()))?
+    .and_hms_opt(0, 0, 0)
+    .unwrap();
+
+    let end_of_month_pacific = Los_Angeles.from_local_datetime(&next_month).single()
+        .ok_or_else(|| ApiError::InternalConversionError("Invalid end of month time".to_string()))?
+        - Duration::seconds(1);
+
+    Ok((start_of_month_pacific.with_timezone(&Utc), end_of_month_pacific.with_timezone(&Utc)))
+}
+
+/// The window of the same length that ends just before `start`
+pub fn get_previous_window(start: DateTime<Utc>, end: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let length = end - start;
+    let previous_end = start - Duration::seconds(1);
+    (previous_end - length, previous_end)
+}
+
+/// Parse an RFC 3339 query parameter, naming the parameter in the error
+pub fn parse_datetime(field: &str, value: &str) -> Result<DateTime<Utc>, ApiError> {
+    value.parse::<DateTime<Utc>>()
+        .map_err(|e| ApiError::BadRequest(format!("Invalid {} datetime format: {}. Expected RFC3339 format (e.g., 2024-01-01T00:00:00Z)", field, e)))
+}
+
+/// Pace as minutes and seconds per kilometre, e.g. "5:07 /km"
+pub fn format_pace(seconds_per_km: f64) -> String {
+    let seconds = seconds_per_km.round() as i64;
+    format!("{}:{:02} /km", seconds / 60, seconds % 60)
+}